@@ -6,6 +6,7 @@ use bevy::{
     utils::HashMap,
 };
 use bevy_asset_loader::prelude::*;
+use bevy_common_assets::ron::RonAssetPlugin;
 use bevy_vello::text::VelloFont;
 use iyes_progress::{
     Progress, ProgressCounter, ProgressPlugin,
@@ -13,7 +14,14 @@ use iyes_progress::{
 };
 // use woodpecker_ui::prelude::;
 
-use crate::states::AppState;
+use crate::{
+    customer_npc::{
+        ContractOffers, CustomerArchetypes, DemandProfiles,
+    },
+    item_catalog::ItemCatalog,
+    machine_shop::MachineCatalog,
+    states::AppState,
+};
 
 pub struct WashCycleAssetsPlugin;
 
@@ -27,6 +35,21 @@ impl Plugin for WashCycleAssetsPlugin {
             ProgressPlugin::new(AppState::AssetLoading)
                 .continue_to(AppState::MainMenu),
             FrameTimeDiagnosticsPlugin,
+            RonAssetPlugin::<ItemCatalog>::new(&[
+                "items.ron",
+            ]),
+            RonAssetPlugin::<CustomerArchetypes>::new(&[
+                "archetypes.ron",
+            ]),
+            RonAssetPlugin::<DemandProfiles>::new(&[
+                "demand.ron",
+            ]),
+            RonAssetPlugin::<ContractOffers>::new(&[
+                "contracts.ron",
+            ]),
+            RonAssetPlugin::<MachineCatalog>::new(&[
+                "shop.ron",
+            ]),
         ))
         .add_loading_state(
             LoadingState::new(AppState::AssetLoading)
@@ -36,6 +59,7 @@ impl Plugin for WashCycleAssetsPlugin {
                 .load_collection::<FontVelloAssets>()
                 .load_collection::<PlayerAssets>()
                 .load_collection::<NavMeshAssets>()
+                .load_collection::<GameDataAssets>()
                 .on_failure_continue_to_state(
                     AppState::ErrorScreen,
                 ),
@@ -171,6 +195,20 @@ pub struct NavMeshAssets {
     pub colormap_002: Handle<Gltf>,
 }
 
+#[derive(AssetCollection, Resource)]
+pub struct GameDataAssets {
+    #[asset(path = "data/laundry.items.ron")]
+    pub item_catalog: Handle<ItemCatalog>,
+    #[asset(path = "data/customers.archetypes.ron")]
+    pub customer_archetypes: Handle<CustomerArchetypes>,
+    #[asset(path = "data/customers.demand.ron")]
+    pub demand_profiles: Handle<DemandProfiles>,
+    #[asset(path = "data/business.contracts.ron")]
+    pub contract_offers: Handle<ContractOffers>,
+    #[asset(path = "data/machines.shop.ron")]
+    pub machine_catalog: Handle<MachineCatalog>,
+}
+
 #[derive(AssetCollection, Resource)]
 pub struct TextureAssets {
     #[asset(path = "tiles1/T_Tiles1_Color.png")]
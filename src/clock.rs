@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+
+use crate::states::{AppState, IsPaused};
+
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GameClock>()
+            .register_type::<Weekday>()
+            .init_resource::<GameClock>()
+            .add_systems(
+                OnEnter(AppState::InGame),
+                reset_clock,
+            )
+            .add_systems(
+                Update,
+                advance_clock
+                    .run_if(in_state(IsPaused::Running)),
+            );
+    }
+}
+
+pub const MINUTES_PER_DAY: f32 = 24. * 60.;
+/// The hour the shop opens its doors
+pub const OPENING_HOUR: f32 = 7.;
+/// The hour the shop stops letting customers in
+pub const CLOSING_HOUR: f32 = 19.;
+
+/// The in-game time of day.
+#[derive(Resource, Reflect, Debug, Clone)]
+#[reflect(Resource)]
+pub struct GameClock {
+    /// Days since the shop first opened
+    pub day: u32,
+    /// Minutes since midnight
+    pub minutes: f32,
+    /// How many in-game minutes pass every real second
+    pub minutes_per_second: f32,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self {
+            day: 0,
+            minutes: OPENING_HOUR * 60.,
+            minutes_per_second: 2.,
+        }
+    }
+}
+
+impl GameClock {
+    /// Hours since midnight, including the fraction of
+    /// the current hour
+    pub fn hour(&self) -> f32 {
+        self.minutes / 60.
+    }
+
+    /// Minutes since midnight before the shop first
+    /// opened. Unlike `minutes` this keeps counting
+    /// across days.
+    pub fn elapsed_minutes(&self) -> f32 {
+        self.day as f32 * MINUTES_PER_DAY + self.minutes
+    }
+
+    /// Whether new customers are let in
+    pub fn is_open(&self) -> bool {
+        (OPENING_HOUR..CLOSING_HOUR).contains(&self.hour())
+    }
+
+    /// The `elapsed_minutes` that lies `minutes` of
+    /// opening hours from now. Nights the shop is
+    /// closed don't count.
+    pub fn open_minutes_after(&self, minutes: f32) -> f32 {
+        let opening = OPENING_HOUR * 60.;
+        let closing = CLOSING_HOUR * 60.;
+        let mut day = self.day;
+        let mut at = self.minutes.max(opening);
+        let mut left = minutes;
+        while at + left > closing {
+            left -= (closing - at).max(0.);
+            day += 1;
+            at = opening;
+        }
+        day as f32 * MINUTES_PER_DAY + at + left
+    }
+
+    /// Moves the clock to opening time on the next day
+    pub fn start_next_day(&mut self) {
+        self.day += 1;
+        self.minutes = OPENING_HOUR * 60.;
+        info!(
+            day = self.day,
+            weekday = ?self.weekday(),
+            "new day"
+        );
+    }
+
+    pub fn weekday(&self) -> Weekday {
+        Weekday::from_day(self.day)
+    }
+
+    /// How many in-game minutes pass in `delta` real
+    /// time
+    pub fn minutes_in(
+        &self,
+        delta: std::time::Duration,
+    ) -> f32 {
+        delta.as_secs_f32() * self.minutes_per_second
+    }
+}
+
+/// The shop opens for the first time on a Monday.
+#[derive(
+    Debug, Reflect, Clone, Copy, PartialEq, Eq, Hash,
+)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    pub const ALL: [Self; 7] = [
+        Self::Monday,
+        Self::Tuesday,
+        Self::Wednesday,
+        Self::Thursday,
+        Self::Friday,
+        Self::Saturday,
+        Self::Sunday,
+    ];
+
+    pub fn from_day(day: u32) -> Self {
+        Self::ALL[day as usize % 7]
+    }
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// The shop closed up for `day`. Triggered once the
+/// last customer is gone, before the next day starts.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DayEnded {
+    pub day: u32,
+}
+
+fn reset_clock(mut clock: ResMut<GameClock>) {
+    *clock = GameClock::default();
+}
+
+/// Stops at midnight. Days only end through the
+/// end of day summary, see `DayCyclePlugin`.
+fn advance_clock(
+    time: Res<Time>,
+    mut clock: ResMut<GameClock>,
+) {
+    clock.minutes = (clock.minutes
+        + clock.minutes_in(time.delta()))
+    .min(MINUTES_PER_DAY);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: f32) -> GameClock {
+        GameClock {
+            minutes,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn open_minutes_within_the_day() {
+        assert_eq!(at(600.).open_minutes_after(60.), 660.);
+    }
+
+    #[test]
+    fn open_minutes_start_at_opening() {
+        let opening = OPENING_HOUR * 60.;
+        assert_eq!(
+            at(300.).open_minutes_after(30.),
+            opening + 30.
+        );
+    }
+
+    #[test]
+    fn open_minutes_up_to_closing_stay_on_the_day() {
+        let closing = CLOSING_HOUR * 60.;
+        assert_eq!(
+            at(closing - 60.).open_minutes_after(60.),
+            closing
+        );
+    }
+
+    #[test]
+    fn open_minutes_skip_the_night() {
+        let opening = OPENING_HOUR * 60.;
+        let closing = CLOSING_HOUR * 60.;
+        assert_eq!(
+            at(closing - 60.).open_minutes_after(120.),
+            MINUTES_PER_DAY + opening + 60.
+        );
+        assert_eq!(
+            at(closing + 60.).open_minutes_after(30.),
+            MINUTES_PER_DAY + opening + 30.
+        );
+    }
+
+    #[test]
+    fn open_minutes_span_several_days() {
+        let opening = OPENING_HOUR * 60.;
+        let open_day = (CLOSING_HOUR - OPENING_HOUR) * 60.;
+        let clock = GameClock {
+            day: 2,
+            minutes: opening,
+            ..default()
+        };
+        assert_eq!(
+            clock.open_minutes_after(open_day * 2. + 10.),
+            4. * MINUTES_PER_DAY + opening + 10.
+        );
+    }
+}
@@ -14,6 +14,7 @@ use leafwing_input_manager::prelude::*;
 use crate::{
     camera::GameCamera,
     game_scene::{Player, PLAYER_FLOATING_HEIGHT},
+    inventory::CheckPockets,
     states::IsPaused,
     widgets::{InventoryModal, OptionsModal},
 };
@@ -30,6 +31,8 @@ impl Plugin for ControlsPlugin {
             (
                 handle_inventory
                     .run_if(in_state(IsPaused::Running)),
+                handle_check_pockets
+                    .run_if(in_state(IsPaused::Running)),
                 apply_controls
                     .in_set(TnuaUserControlsSystemSet)
                     .run_if(in_state(IsPaused::Running)),
@@ -57,6 +60,7 @@ pub enum PlayerAction {
     //
     Pause,
     Inventory,
+    CheckPockets,
     // Abilities
     Ability1,
     Ability2,
@@ -103,6 +107,11 @@ impl PlayerAction {
         //
 
         input_map.insert(Inventory, KeyCode::KeyI);
+        input_map.insert(CheckPockets, KeyCode::KeyC);
+        input_map.insert(
+            CheckPockets,
+            GamepadButtonType::Select,
+        );
 
         // Abilities
         input_map.insert(Ability1, KeyCode::KeyQ);
@@ -155,6 +164,23 @@ fn handle_inventory(
     }
 }
 
+fn handle_check_pockets(
+    query: Query<
+        (Entity, &ActionState<PlayerAction>),
+        With<Player>,
+    >,
+    mut commands: Commands,
+) {
+    for (player, player_action) in &query {
+        if player_action
+            .just_pressed(&PlayerAction::CheckPockets)
+        {
+            commands
+                .trigger(CheckPockets { holder: player });
+        }
+    }
+}
+
 fn apply_controls(
     keyboard: Res<ButtonInput<KeyCode>>,
     mut query: Query<
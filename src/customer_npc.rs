@@ -5,15 +5,16 @@ use bevy::{
     color::palettes::tailwind::*,
     prelude::*,
     render::{
-        mesh::VertexAttributeValues,
         render_asset::RenderAssetUsages,
-        render_resource::PrimitiveTopology,
         view::RenderLayers,
     },
     utils::HashSet,
 };
 use bevy_mod_picking::prelude::*;
-use rand::{seq::IteratorRandom, Rng};
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    Rng,
+};
 use std::{
     ops::Deref,
     time::{Duration, Instant},
@@ -24,19 +25,86 @@ use vello::wgpu::{
 use woodpecker_ui::prelude::*;
 
 use crate::{
-    assets::{FontAssets, FontVelloAssets, PlayerAssets},
-    game_scene::Player,
-    inventory::{Inventory, Item, ProcessedState},
-    navmesh::{Object, Path, SpawnObstacle},
+    assets::{
+        FontAssets, FontVelloAssets, GameDataAssets,
+        PlayerAssets,
+    },
+    clock::GameClock,
+    game_scene::{CurrentLevel, Player},
+    inventory::{
+        HeldBy, InPocket, Inventory, InventoryTransfer,
+        Item, ItemFilter, ItemsTransferred, ProcessedState,
+    },
+    item_catalog::ItemCatalog,
+    navmesh::{Destination, Object, Path, SpawnObstacle},
     persistent_id::PersistentId,
+    processing::LaundryPipeline,
+    rng::{GameRng, RngStream},
     states::{AppState, GameMode, IsPaused},
+    steering::{
+        waypoint_reached, Crowd, Steering, SteeringSystems,
+    },
     widgets::{self, *},
 };
 
+mod archetype;
+mod claim_ticket;
+mod contract;
+mod demand;
+mod order;
+mod patience;
+mod queue;
+mod satisfaction;
+mod self_service;
+mod spawner;
+
+use archetype::ArchetypePlugin;
+pub use archetype::{
+    CustomerArchetype, CustomerArchetypes,
+};
+use claim_ticket::{issue_claim_ticket, ClaimTicketPlugin};
+pub use claim_ticket::ClaimTicket;
+use contract::ContractPlugin;
+pub use contract::{
+    AcceptContract, Contract, ContractEnded,
+    ContractOffers, ContractTerms,
+};
+use demand::DemandPlugin;
+pub use demand::{DemandPeak, DemandProfiles, LevelDemand};
+use order::{place_order, OrderPlugin};
+pub use order::{Order, OrderSettled, ServiceTier};
+use patience::PatiencePlugin;
+pub use patience::{CustomerWalkedOut, Mood, Patience};
+use queue::{
+    join_shortest_queue, staffed_counter, JoinQueueSystems,
+    QueuePlugin,
+};
+pub use queue::{CounterQueue, QueuedAt};
+use satisfaction::{average_condition, SatisfactionPlugin};
+pub use satisfaction::{
+    CustomerServed, Reputation, Satisfaction,
+};
+use self_service::SelfServicePlugin;
+pub use self_service::{SelfService, SelfServiceCyclePaid};
+use spawner::{sample_spawn_point, SpawnerPlugin};
+pub use spawner::SpawnerWeight;
+
 pub struct CustomerNpcPlugin;
 
 impl Plugin for CustomerNpcPlugin {
     fn build(&self, app: &mut App) {
+        app.add_plugins((
+            SatisfactionPlugin,
+            PatiencePlugin,
+            QueuePlugin,
+            ArchetypePlugin,
+            DemandPlugin,
+            SpawnerPlugin,
+            ClaimTicketPlugin,
+            SelfServicePlugin,
+            OrderPlugin,
+            ContractPlugin,
+        ));
         app.register_type::<CustomerNpcSpawner>()
             .register_type::<CustomerDropoffLocation>()
             .register_type::<PlayerReceiveFromCustomerLocation>()
@@ -44,10 +112,13 @@ impl Plugin for CustomerNpcPlugin {
             .add_systems(
                 Update,
                 (
-                    move_customer,
+                    move_customer.after(SteeringSystems),
+                    animate_customer_movement
+                        .after(SteeringSystems),
                     detect_customer_dropoff,
                     detect_pickup,
                     detect_player_return_to_customer_pickup,
+                    collect_returned_laundry,
                 )
                     .run_if(in_state(IsPaused::Running)),
             )
@@ -141,17 +212,21 @@ impl From<CustomerNpcAnimationNames>
 #[derive(Component)]
 pub struct CustomerNpc {
     pub gltf: Handle<Gltf>,
-    // TODO: when Items become Entitys, remove this field.
-    pub expected_number_items_to_leave: usize,
+    /// The name of the `CustomerArchetype` this
+    /// customer was spawned from
+    pub archetype: String,
+    /// Multiplier on the `base_price` of their items
+    pub payment: f32,
 }
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 struct TheLight;
 
+/// Where customers come in from the street.
 #[derive(Component, Reflect)]
 #[reflect(Component)]
-struct CustomerNpcSpawner;
+pub struct CustomerNpcSpawner;
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
@@ -167,97 +242,49 @@ pub struct CustomerNpcSpawnEvent;
 fn spawn_customer_npc(
     trigger: Trigger<CustomerNpcSpawnEvent>,
     spawners: Query<
-        (Entity, &Handle<Mesh>, &Parent),
+        (
+            &Handle<Mesh>,
+            &GlobalTransform,
+            Option<&SpawnerWeight>,
+        ),
         With<CustomerNpcSpawner>,
     >,
-    transforms: Query<&Transform>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut images: ResMut<Assets<Image>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    dropoff_locations: Query<
-        (Entity, &Transform),
-        With<CustomerDropoffLocation>,
-    >,
+    mut queues: Query<(Entity, &mut CounterQueue)>,
     player_assets: Res<PlayerAssets>,
     gltfs: Res<Assets<Gltf>>,
+    pipeline: Res<LaundryPipeline>,
+    game_data: Res<GameDataAssets>,
+    item_catalogs: Res<Assets<ItemCatalog>>,
+    customer_archetypes: Res<Assets<CustomerArchetypes>>,
+    mut game_rng: ResMut<GameRng>,
 ) {
-    // TODO: bevy 0.15: UniformMeshSampling is now a
-    // thing, we can remove this Rectangle
-    // assumption code
-    let Ok((spawner_entity, spawner_mesh_handle, parent)) =
-        spawners.get_single()
-    else {
-        warn!("only expected one spawner");
-        return;
-    };
-
-    let Some(spawner_mesh) =
-        meshes.get(spawner_mesh_handle)
-    else {
-        warn!("no available mesh");
-        return;
-    };
-
-    if !matches!(
-        spawner_mesh.primitive_topology(),
-        PrimitiveTopology::TriangleList
-    ) {
-        warn!("Spawner is not a TriangleList");
-        return;
-    }
-    // dbg!(spawner_mesh);
-    // let Some(indices) = spawner_mesh.indices() else
-    // {     warn!("indices unavailable");
-    //     return;
-    // };
-    let Some(VertexAttributeValues::Float32x3(positions)) =
-        spawner_mesh.attribute(Mesh::ATTRIBUTE_POSITION)
-    else {
-        warn!("can't get ATTRIBUTE_POSITION from Mesh");
-        return;
-    };
-
-    if positions.len() != 4 {
-        warn!("mesh is not a Rectangle, can not be other shapes until 0.15");
-        return;
-    }
-
-    let Some(negative_corner) =
-        positions.iter().find(|arr| {
-            arr[0].is_sign_negative()
-                && arr[2].is_sign_negative()
-        })
+    let Some(item_catalog) =
+        item_catalogs.get(&game_data.item_catalog)
     else {
-        warn!("unable to find negative corner");
+        warn!("item catalog is not loaded");
         return;
     };
-    let Some(positive_corner) =
-        positions.iter().find(|arr| {
-            arr[0].is_sign_positive()
-                && arr[2].is_sign_positive()
-        })
+    let Some(customer_archetypes) = customer_archetypes
+        .get(&game_data.customer_archetypes)
     else {
-        warn!("unable to find positive corner");
+        warn!("customer archetypes are not loaded");
         return;
     };
 
-    let rect = Rectangle::from_corners(
-        Vec2::new(negative_corner[0], negative_corner[2]),
-        Vec2::new(positive_corner[0], positive_corner[2]),
-    );
-
-    let Ok(transform) = transforms.get(parent.get()) else {
-        warn!(
-            "Parent of spawn mesh didn't have a Transform"
-        );
+    let Some(spawn_point) = sample_spawn_point(
+        &spawners,
+        &meshes,
+        game_rng.stream(RngStream::Spawning),
+    ) else {
+        warn!("no spawner to spawn customers at");
         return;
     };
-    let mut rng = rand::thread_rng();
-    let sample = rect.sample_interior(&mut rng);
-    let mut new_transform = transform.clone();
-    new_transform.translation.x += sample.x;
-    new_transform.translation.z += sample.y;
+    let new_transform =
+        Transform::from_translation(spawn_point);
 
     let debug_material = materials.add(StandardMaterial {
         base_color_texture: Some(
@@ -266,38 +293,137 @@ fn spawn_customer_npc(
         ..default()
     });
 
-    let Ok((dropoff_entity, dropoff_transform)) =
-        dropoff_locations.get_single()
+    if queues.is_empty() {
+        warn!("no dropoff locations to queue at");
+        return;
+    }
+    let mut rng = game_rng.stream(RngStream::Customers);
+    let Some(archetype) =
+        customer_archetypes.choose(&mut rng)
     else {
-        warn!("Only one dropoff location is supported");
+        warn!("no customer archetypes to choose from");
         return;
     };
     let (character_key, random_character) =
         player_assets.character_gltfs
           .iter()
+          .filter(|(key, _)| {
+              archetype.models.is_empty()
+                  || archetype.models.contains(*key)
+          })
           .choose(&mut rng)
+          .or_else(|| {
+              warn!(archetype = archetype.name, "no matching character models");
+              player_assets.character_gltfs.iter().choose(&mut rng)
+          })
           .expect("expect random character selection to always succeed");
-    let random_character_gltf =
-        gltfs.get(random_character).unwrap();
 
     let persistent_id = PersistentId::new();
-    let items = vec![
-        Item {
-            name: "suit".to_string(),
-            owner: Some(persistent_id.clone()),
-            state: ProcessedState::Unprocessed,
-        },
-        Item {
-            name: "pen".to_string(),
-            owner: Some(persistent_id.clone()),
-            state: ProcessedState::Unprocessed,
-        },
-    ];
-    commands
+    let Some(customer) = spawn_customer(
+        &mut commands,
+        archetype,
+        random_character,
+        &gltfs,
+        new_transform,
+        persistent_id.clone(),
+    ) else {
+        return;
+    };
+
+    // some customers skip the counter and run the
+    // machines themselves, the rest line up
+    if rng.gen_bool(archetype.self_service_chance) {
+        commands
+            .entity(customer)
+            .insert(SelfService::default());
+    } else if let Some(counter) =
+        join_shortest_queue(customer, &mut queues)
+    {
+        commands.entity(customer).insert((
+            QueuedAt {
+                counter,
+                slot: None,
+            },
+            archetype.choose_tier(&mut rng),
+        ));
+    }
+
+    for definition in
+        archetype.choose_items(item_catalog, &mut rng)
+    {
+        let order =
+            pipeline.order(&definition.stages, |_| {
+                rng.gen_bool(
+                    archetype.optional_stage_chance,
+                )
+            });
+        let garment = commands
+            .spawn((
+                Name::new(format!(
+                    "Item: {}",
+                    definition.name
+                )),
+                Item::new(
+                    definition,
+                    Some(persistent_id.clone()),
+                    order,
+                    definition.random_color(&mut rng),
+                ),
+                HeldBy(customer),
+            ))
+            .id();
+
+        // customers don't always check their pockets
+        // before handing over their laundry
+        if !definition.has_pockets
+            || !rng.gen_bool(archetype.pocket_chance)
+        {
+            continue;
+        }
+        let Some(pocket_definition) =
+            item_catalog.pocket_items().choose(&mut rng)
+        else {
+            continue;
+        };
+        commands.spawn((
+            Name::new(format!(
+                "Item: {}",
+                pocket_definition.name
+            )),
+            Item::new(
+                pocket_definition,
+                Some(persistent_id.clone()),
+                vec![],
+                pocket_definition.random_color(&mut rng),
+            ),
+            HeldBy(customer),
+            InPocket(garment),
+        ));
+    }
+}
+
+/// Spawns a customer of `archetype` wearing `gltf`.
+fn spawn_customer(
+    commands: &mut Commands,
+    archetype: &CustomerArchetype,
+    gltf: &Handle<Gltf>,
+    gltfs: &Assets<Gltf>,
+    transform: Transform,
+    persistent_id: PersistentId,
+) -> Option<Entity> {
+    let Some(character_gltf) = gltfs.get(gltf) else {
+        warn!("customer character is not loaded");
+        return None;
+    };
+
+    let customer = commands
         .spawn((
-            Name::new("CustomerNpc"),
+            Name::new(format!(
+                "CustomerNpc: {}",
+                archetype.name
+            )),
             SpatialBundle {
-                transform: new_transform,
+                transform,
                 ..default()
             },
             // PbrBundle {
@@ -308,42 +434,80 @@ fn spawn_customer_npc(
             // new_transform,     ..default()
             // },
             CustomerNpc {
-                gltf: random_character.clone(),
-                expected_number_items_to_leave: items.len(),
-            },
-            Object(Some(dropoff_entity)),
-            Path {
-                current: dropoff_transform.translation,
-                next: vec![],
+                gltf: gltf.clone(),
+                archetype: archetype.name.clone(),
+                payment: archetype.payment,
             },
             Collider::capsule(0.5, 1.),
+            Crowd { radius: 0.5 },
+            Steering::new(archetype.walk_speed),
             Inventory {
-                max_item_count: 5,
-                items,
+                max_volume: archetype.max_volume,
             },
+            Patience::new(archetype.patience),
             persistent_id,
         ))
         .with_children(|builder| {
             builder.spawn(SceneBundle {
-                scene: random_character_gltf.scenes[0]
-                    .clone(),
+                scene: character_gltf.scenes[0].clone(),
                 transform: Transform::from_xyz(0., 0.5, 0.),
                 ..default()
             });
-        });
+        })
+        .id();
+
+    Some(customer)
 }
 
+/// Customers that see this many people lined up at
+/// every counter go somewhere else
+const MAX_QUEUE_LENGTH: usize = 5;
+
 fn customer_spawn_cycle(
     mut commands: Commands,
+    reputation: Res<Reputation>,
+    clock: Res<GameClock>,
+    time: Res<Time>,
+    current_level: Option<Res<CurrentLevel>>,
+    game_data: Res<GameDataAssets>,
+    demand_profiles: Res<Assets<DemandProfiles>>,
+    queues: Query<&CounterQueue>,
+    mut game_rng: ResMut<GameRng>,
     // customers: Query<&CustomerNpc>,
 ) {
     // if customers.iter().len() > 1 {
     //     return;
     // }
-    // 1 customer per 60 * n seconds
-    // because of FixedUpdate rate
-    let spawn_rate = 1. / (60. * 10.);
-    let mut rng = rand::thread_rng();
+    let Some(demand) = current_level
+        .zip(
+            demand_profiles.get(&game_data.demand_profiles),
+        )
+        .and_then(|(level, profiles)| {
+            profiles.for_level(&level.0)
+        })
+    else {
+        warn_once!("no customer demand for this level");
+        return;
+    };
+    // past closing the doors stay shut to new
+    // customers, the ones inside still get served
+    if !clock.is_open() {
+        return;
+    }
+    if queues.iter().all(|queue| {
+        queue.customers.len() >= MAX_QUEUE_LENGTH
+    }) {
+        return;
+    }
+
+    // the chance of a customer showing up during this
+    // FixedUpdate tick
+    let spawn_rate = demand
+        .rate(clock.weekday(), clock.hour())
+        * clock.minutes_in(time.delta())
+        / 60.
+        * reputation.customer_rate();
+    let rng = game_rng.stream(RngStream::Spawning);
     // TODO: when should this become rng.random (due
     // to gen blocks)
     if rng.r#gen::<f32>() < spawn_rate {
@@ -391,12 +555,57 @@ fn move_customer(
             &mut Path,
             Entity,
             &mut Object,
+            &Steering,
         ),
         With<CustomerNpc>,
     >,
     time: Res<Time>,
+    leaving: Query<&Leaving>,
+) {
+    for (
+        mut npc_transform,
+        mut target,
+        entity,
+        mut npc,
+        steering,
+    ) in npc_query.iter_mut()
+    {
+        npc_transform.translation +=
+            steering.velocity * time.delta_seconds();
+
+        if waypoint_reached(
+            &target,
+            npc_transform.translation,
+        ) {
+            if let Some(next) = target.next.pop() {
+                target.current = next;
+            } else {
+                commands.entity(entity).remove::<Path>();
+                npc.0.take();
+                // npc has made it to final target
+                if leaving.get(entity).is_ok() {
+                    commands
+                        .entity(entity)
+                        .despawn_recursive();
+                }
+            }
+        }
+    }
+}
+
+/// Below this speed customers stand idle
+const WALKING_SPEED: f32 = 0.1;
+
+/// Faces customers the way they are moving and blends
+/// between walking and idling as they speed up and
+/// slow down.
+fn animate_customer_movement(
+    customers: Query<
+        (Entity, &Steering),
+        With<CustomerNpc>,
+    >,
     children: Query<&Children>,
-    mut transforms: Query<
+    mut characters: Query<
         (
             &mut Transform,
             &mut AnimationTransitions,
@@ -404,73 +613,57 @@ fn move_customer(
         ),
         Without<CustomerNpc>,
     >,
-    leaving: Query<&Leaving>,
 ) {
-    for (mut npc_transform, mut target, entity, mut npc) in
-        npc_query.iter_mut()
-    {
-        let move_direction =
-            target.current - npc_transform.translation;
-        npc_transform.translation += move_direction
-            .normalize()
-            * time.delta_seconds()
-            * 10.0;
-
+    for (entity, steering) in &customers {
         // if we have a child that is an animated
         // character face them in a direction
         let Some(character_entity) = children
             .iter_descendants(entity)
-            .find(|e| transforms.get(*e).is_ok())
+            .find(|e| characters.contains(*e))
         else {
             continue;
         };
-
-        let (
+        let Ok((
             mut transform,
             mut animation_transitions,
             mut player,
-        ) = transforms.get_mut(character_entity).unwrap();
-        let mut new_direction = -move_direction;
-        new_direction.y = 0.;
-        transform
-            .look_to(new_direction.normalize(), Vec3::Y);
-
-        if npc_transform
-            .translation
-            .distance(target.current)
-            < 0.1
-        {
-            if let Some(next) = target.next.pop() {
+        )) = characters.get_mut(character_entity)
+        else {
+            continue;
+        };
+
+        let velocity = steering.velocity.with_y(0.);
+        let speed = velocity.length();
+        let walking = animation_transitions
+            .get_main_animation()
+            == Some(CustomerNpcAnimationNames::Walk.into());
+
+        if speed > WALKING_SPEED {
+            transform.look_to(-velocity / speed, Vec3::Y);
+            if !walking {
                 animation_transitions
                     .play(
                         &mut player,
                         CustomerNpcAnimationNames::Walk
                             .into(),
-                        Duration::from_secs(0),
+                        Duration::from_millis(250),
                     )
                     .repeat();
-                target.current = next;
-            } else {
-                commands.entity(entity).remove::<Path>();
-                let target_entity = npc.0.take().unwrap();
-                // npc has made it to final target,
-                // play idle animation
-                // entity
-                if leaving.get(entity).is_ok() {
-                    commands
-                        .entity(entity)
-                        .despawn_recursive();
-                } else {
-                    animation_transitions
-                        .play(
-                            &mut player,
-                            CustomerNpcAnimationNames::Idle
-                                .into(),
-                            Duration::from_secs(1),
-                        )
-                        .repeat();
-                }
             }
+            // match the stride to how fast they go
+            if let Some(walk) = player.animation_mut(
+                CustomerNpcAnimationNames::Walk.into(),
+            ) {
+                walk.set_speed(speed / steering.max_speed);
+            }
+        } else if walking {
+            animation_transitions
+                .play(
+                    &mut player,
+                    CustomerNpcAnimationNames::Idle.into(),
+                    Duration::from_secs(1),
+                )
+                .repeat();
         }
     }
 }
@@ -481,9 +674,10 @@ fn detect_customer_dropoff(
         With<CustomerDropoffLocation>,
     >,
     customers: Query<
-        (Entity, &Inventory),
+        (Entity, &QueuedAt),
         With<CustomerNpc>,
     >,
+    items: Query<(&Item, &HeldBy)>,
     // TODO: a dropoff point should likely be associated
     // with some specific lights, but for now its just
     // "all of them"
@@ -492,232 +686,386 @@ fn detect_customer_dropoff(
         With<TheLight>,
     >,
 ) {
-    for entities_on_sensor in &dropoff_sensors {
-        // if a customer is standing on the sensor and has
-        // items in their inventory, then they are
-        // "ready to dropoff"
-        let customer =
-            customers.iter().find(|(entity, inventory)| {
-                entities_on_sensor.contains(entity)
-                    && inventory.items.len() > 0
-                    && inventory.items.iter().any(|item| item.state == ProcessedState::Unprocessed)
-            });
+    // if the customer at the front of any line is
+    // standing on the sensor and has items in their
+    // inventory, then they are "ready to dropoff"
+    let customer_ready =
+        dropoff_sensors.iter().any(|entities_on_sensor| {
+            customers.iter().any(|(entity, queued_at)| {
+                queued_at.is_at_front()
+                    && entities_on_sensor.contains(&entity)
+                    && items.iter().any(
+                        |(item, held_by)| {
+                            held_by.0 == entity
+                                && !item.is_finished()
+                        },
+                    )
+            })
+        });
 
-        if customer.is_some() {
-            for mut visibility in &mut ready_lights {
-                *visibility = Visibility::Visible;
-            }
+    for mut visibility in &mut ready_lights {
+        *visibility = if customer_ready {
+            Visibility::Visible
         } else {
-            for mut visibility in &mut ready_lights {
-                *visibility = Visibility::Hidden;
-            }
-        }
+            Visibility::Hidden
+        };
     }
 }
 
+/// A customer that came back with a `ClaimTicket`
 #[derive(Component)]
 struct WaitingForStuffBack;
 
 fn detect_pickup(
     query: Query<
-        &CollidingEntities,
+        (Entity, &CollidingEntities, &GlobalTransform),
         With<CustomerDropoffLocation>,
     >,
     pickup_locations: Query<
-        &CollidingEntities,
+        (&CollidingEntities, &GlobalTransform),
         With<PlayerReceiveFromCustomerLocation>,
     >,
-    mut player: Query<
-        (Entity, &mut Inventory),
+    player: Query<
+        Entity,
         (With<Player>, Without<CustomerNpc>),
     >,
-    mut customers: Query<
-        (Entity, &mut Inventory),
-        With<CustomerNpc>,
+    customers: Query<
+        (
+            Entity,
+            &CustomerNpc,
+            &PersistentId,
+            &QueuedAt,
+            Option<&ServiceTier>,
+            &Patience,
+        ),
+        (Without<WaitingForStuffBack>, Without<Leaving>),
     >,
+    items: Query<(&Item, &HeldBy)>,
     mut ready_lights: Query<
         &mut Visibility,
         With<TheLight>,
     >,
+    spawner_meshes: Query<
+        (Entity, &Parent),
+        With<CustomerNpcSpawner>,
+    >,
+    transforms: Query<&Transform>,
+    clock: Res<GameClock>,
     mut commands: Commands,
 ) {
-    let Ok(pickup_colliding_entities) =
-        pickup_locations.get_single()
-    else {
-        warn_once!("expected exactly 1 pickup location");
+    let Ok(player_entity) = player.get_single() else {
+        warn!("expected exactly 1 player");
         return;
     };
 
-    let Ok((player_entity, mut player_inventory)) =
-        player.get_single_mut()
+    let Some(counter) = staffed_counter(
+        player_entity,
+        &pickup_locations,
+        &query,
+    ) else {
+        return;
+    };
+    let Ok((_, sensor_colliding_entities, _)) =
+        query.get(counter)
     else {
-        warn!("expected exactly 1 player");
         return;
     };
 
-    let customer_set = customers
-        .iter()
-        .map(|(entity, _)| entity)
-        .collect::<HashSet<Entity>>();
-    for sensor_colliding_entities in &query {
-        let Some((customer_entity, mut customer_inventory)) =
-            customers.iter_mut().find(|(entity, _)| {
-                sensor_colliding_entities.contains(entity)
-            })
-        else {
-            continue;
-        };
+    // only the customer at the front of the line gets
+    // served
+    let Some((
+        customer_entity,
+        customer,
+        customer_id,
+        _,
+        tier,
+        patience,
+    )) = customers.iter().find(
+        |(entity, _, _, queued_at, _, _)| {
+            queued_at.counter == counter
+                && queued_at.is_at_front()
+                && sensor_colliding_entities
+                    .contains(entity)
+        },
+    ) else {
+        return;
+    };
 
-        if pickup_colliding_entities
-            .contains(&player_entity)
-        {
-            let available_space = player_inventory
-                .max_item_count
-                - player_inventory.items.len();
-
-            // take all items, or only the amount that would
-            // fit in the available space in the player's
-            // inventory, whichever is smaller.
-            //
-            // this can be an empty range, resulting in no
-            // items transferring
-            let item_range = 0..(customer_inventory
-                .items
-                .len()
-                .min(available_space));
-
-            let transition_items =
-                customer_inventory.items.drain(item_range);
-
-            player_inventory.items.extend(transition_items);
-            commands
-                .entity(customer_entity)
-                .insert(WaitingForStuffBack);
-        }
+    let still_holding_laundry =
+        items.iter().any(|(item, held_by)| {
+            held_by.0 == customer_entity
+                && !item.is_finished()
+        });
+    if still_holding_laundry {
+        // take all unfinished items, or only the amount
+        // that would fit in the available space in the
+        // player's inventory, whichever is smaller.
+        commands.trigger(InventoryTransfer {
+            from: customer_entity,
+            to: player_entity,
+            filter: ItemFilter {
+                finished: Some(false),
+                ..default()
+            },
+            all_or_nothing: false,
+            max_weight: None,
+        });
+        return;
     }
+
+    // everything has been handed over, the customer
+    // places an order, takes a ticket and comes back
+    // for it by the deadline
+    // whatever was in the pockets isn't laundry and
+    // isn't charged for
+    let order_items = items
+        .iter()
+        .map(|(item, _)| item)
+        .filter(|item| {
+            item.owner.as_ref() == Some(customer_id)
+                && item.washable
+        })
+        .collect::<Vec<_>>();
+    if !order_items.is_empty() {
+        let deadline = place_order(
+            &mut commands,
+            customer,
+            customer_id,
+            tier.copied().unwrap_or_default(),
+            &order_items,
+            &clock,
+        );
+        issue_claim_ticket(
+            &mut commands,
+            customer,
+            customer_id,
+            deadline,
+            patience.waited,
+        );
+    }
+    leave_shop(
+        &mut commands,
+        customer_entity,
+        &spawner_meshes,
+        &transforms,
+    );
 }
 
 #[derive(Component)]
 pub struct Leaving;
 
-// TODO: customer should expect all items
 fn detect_player_return_to_customer_pickup(
     dropoff_locations: Query<
-        &CollidingEntities,
+        (Entity, &CollidingEntities, &GlobalTransform),
         With<CustomerDropoffLocation>,
     >,
     pickup_locations: Query<
-        &CollidingEntities,
+        (&CollidingEntities, &GlobalTransform),
         With<PlayerReceiveFromCustomerLocation>,
     >,
-    mut player: Query<
-        (Entity, &mut Inventory),
+    player: Query<
+        Entity,
         (With<Player>, Without<CustomerNpc>),
     >,
-    mut customers: Query<
-        (
-            Entity,
-            &mut Inventory,
-            &PersistentId,
-            &CustomerNpc,
-        ),
-        (With<WaitingForStuffBack>,),
+    customers: Query<
+        (Entity, &PersistentId, &QueuedAt),
+        (With<WaitingForStuffBack>, Without<Leaving>),
     >,
-    mut ready_lights: Query<
-        &mut Visibility,
-        With<TheLight>,
+    mut commands: Commands,
+) {
+    let Ok(player_entity) = player.get_single() else {
+        warn!("expected exactly 1 player");
+        return;
+    };
+
+    let Some(counter) = staffed_counter(
+        player_entity,
+        &pickup_locations,
+        &dropoff_locations,
+    ) else {
+        return;
+    };
+    let Ok((_, sensor_colliding_entities, _)) =
+        dropoff_locations.get(counter)
+    else {
+        return;
+    };
+
+    for (customer_entity, customer_persistent_id, _) in
+        customers.iter().filter(|(entity, _, queued_at)| {
+            queued_at.counter == counter
+                && queued_at.is_at_front()
+                && sensor_colliding_entities
+                    .contains(entity)
+        })
+    {
+        commands.trigger(InventoryTransfer {
+            from: player_entity,
+            to: customer_entity,
+            filter: ItemFilter {
+                owner: Some(customer_persistent_id.clone()),
+                finished: Some(true),
+                ..default()
+            },
+            all_or_nothing: false,
+            max_weight: None,
+        });
+    }
+}
+
+/// The customer leaves once every item they own is
+/// back in their hands and has been through every
+/// stage they ordered. Checked after each
+/// `InventoryTransfer` to them has gone through.
+fn collect_returned_laundry(
+    mut transferred: EventReader<ItemsTransferred>,
+    customers: Query<
+        (&PersistentId, &Patience),
+        (With<WaitingForStuffBack>, Without<Leaving>),
     >,
+    items: Query<(&Item, &HeldBy)>,
     spawner_meshes: Query<
         (Entity, &Parent),
         With<CustomerNpcSpawner>,
     >,
     transforms: Query<&Transform>,
+    children: Query<&Children>,
+    mut animation_players: Query<(
+        &mut AnimationTransitions,
+        &mut AnimationPlayer,
+    )>,
+    mut served: EventWriter<CustomerServed>,
     mut commands: Commands,
 ) {
-    let Ok(pickup_colliding_entities) =
-        pickup_locations.get_single()
+    let receivers = transferred
+        .read()
+        .map(|transfer| transfer.to)
+        .collect::<HashSet<Entity>>();
+    for customer_entity in receivers {
+        let Ok((customer_persistent_id, patience)) =
+            customers.get(customer_entity)
+        else {
+            continue;
+        };
+
+        let has_outstanding_items =
+            items.iter().any(|(item, held_by)| {
+                item.owner.as_ref()
+                    == Some(customer_persistent_id)
+                    && (held_by.0 != customer_entity
+                        || !item.is_finished())
+            });
+        if has_outstanding_items {
+            continue;
+        }
+
+        let average_condition = average_condition(
+            items.iter().map(|(item, _)| item).filter(
+                |item| {
+                    item.owner.as_ref()
+                        == Some(customer_persistent_id)
+                },
+            ),
+        );
+        let satisfaction =
+            Satisfaction::from_condition(average_condition);
+        info!(
+            ?customer_entity,
+            average_condition,
+            ?satisfaction,
+            "customer served"
+        );
+        served.send(CustomerServed {
+            customer: customer_entity,
+            customer_id: customer_persistent_id.clone(),
+            average_condition,
+            satisfaction,
+            waited: patience.waited,
+        });
+        let reaction = match satisfaction {
+            Satisfaction::Unhappy => {
+                CustomerNpcAnimationNames::EmoteNo
+            }
+            Satisfaction::Satisfied
+            | Satisfaction::Delighted => {
+                CustomerNpcAnimationNames::EmoteYes
+            }
+        };
+        play_customer_animation(
+            customer_entity,
+            reaction,
+            &children,
+            &mut animation_players,
+        );
+
+        leave_shop(
+            &mut commands,
+            customer_entity,
+            &spawner_meshes,
+            &transforms,
+        );
+    }
+}
+
+/// Sends a customer back out the way they came in.
+fn leave_shop(
+    commands: &mut Commands,
+    customer: Entity,
+    spawner_meshes: &Query<
+        (Entity, &Parent),
+        With<CustomerNpcSpawner>,
+    >,
+    transforms: &Query<&Transform>,
+) {
+    let Some((exit_entity, exit_parent)) =
+        spawner_meshes.iter().next()
     else {
-        warn_once!("expected exactly 1 pickup location");
+        warn!("no way to leave");
         return;
     };
-
-    let Ok((player_entity, mut player_inventory)) =
-        player.get_single_mut()
+    let Ok(exit_transform) =
+        transforms.get(exit_parent.get())
     else {
-        warn!("expected exactly 1 player");
+        warn!("no exit for customer");
         return;
     };
+    info!(
+        ?exit_entity,
+        location = ?exit_transform.translation,
+        "trying to exit"
+    );
+    commands
+        .entity(customer)
+        .insert(Object(Some(exit_entity)))
+        .insert(Destination(exit_transform.translation))
+        .insert(Leaving);
+}
 
-    for sensor_colliding_entities in &dropoff_locations {
-        for (
-            customer_entity,
-            mut customer_inventory,
-            customer_persistent_id,
-            customer_npc,
-        ) in customers.iter_mut().filter(
-            |(entity, _, _, _)| {
-                sensor_colliding_entities.contains(entity)
-            },
-        ) {
-            if pickup_colliding_entities
-                .contains(&player_entity)
-            {
-                let available_space = customer_inventory
-                    .max_item_count
-                    - customer_inventory.items.len();
-                for item_to_move in player_inventory
-                    .items
-                    .extract_if(|item| {
-                        item.owner.as_ref()
-                            == Some(customer_persistent_id)
-                            && item.state
-                                == ProcessedState::Processed
-                    })
-                    .take(available_space)
-                {
-                    customer_inventory
-                        .items
-                        .push(item_to_move);
-                }
-
-                if customer_inventory.items.len()
-                    == customer_npc
-                        .expected_number_items_to_leave
-                    && customer_inventory.items.iter().all(
-                        |item| {
-                            item.state
-                                == ProcessedState::Processed
-                        },
-                    )
-                {
-                    let Some((exit_entity, exit_parent)) =
-                        spawner_meshes.iter().next()
-                    else {
-                        warn!("no way to leave");
-                        return;
-                    };
-                    let Ok(exit_transform) =
-                        transforms.get(exit_parent.get())
-                    else {
-                        warn!("no exit for customer");
-                        return;
-                    };
-                    info!(
-                        ?exit_entity,
-                        location = ?exit_transform.translation,
-                        "trying to exit"
-                    );
-                    commands
-                        .entity(customer_entity)
-                        .insert(Object(Some(exit_entity)))
-                        .insert(Path {
-                            current: exit_transform
-                                .translation,
-                            next: vec![],
-                        })
-                        .insert(Leaving);
-                }
-            }
-        }
-    }
+/// Plays `animation` once on the animated character
+/// somewhere below `customer`.
+fn play_customer_animation(
+    customer: Entity,
+    animation: CustomerNpcAnimationNames,
+    children: &Query<&Children>,
+    animation_players: &mut Query<(
+        &mut AnimationTransitions,
+        &mut AnimationPlayer,
+    )>,
+) {
+    let Some(character_entity) = children
+        .iter_descendants(customer)
+        .find(|e| animation_players.contains(*e))
+    else {
+        return;
+    };
+    let Ok((mut transitions, mut player)) =
+        animation_players.get_mut(character_entity)
+    else {
+        return;
+    };
+    transitions.play(
+        &mut player,
+        animation.into(),
+        Duration::from_millis(250),
+    );
 }
@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    Rng,
+};
+use serde::Deserialize;
+
+use super::ServiceTier;
+use crate::item_catalog::{ItemCatalog, ItemDefinition};
+
+pub struct ArchetypePlugin;
+
+impl Plugin for ArchetypePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<CustomerArchetype>()
+            .add_systems(Update, clamp_chances);
+    }
+}
+
+/// The kinds of customers that walk in the door.
+///
+/// Loaded from `assets/data/customers.archetypes.ron`.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct CustomerArchetypes {
+    pub archetypes: Vec<CustomerArchetype>,
+}
+
+impl CustomerArchetypes {
+    /// Picks an archetype, favoring those with a
+    /// higher `weight`
+    pub fn choose(
+        &self,
+        rng: &mut impl Rng,
+    ) -> Option<&CustomerArchetype> {
+        self.archetypes
+            .choose_weighted(rng, |archetype| {
+                archetype.weight
+            })
+            .ok()
+    }
+
+    pub fn get(
+        &self,
+        name: &str,
+    ) -> Option<&CustomerArchetype> {
+        self.archetypes
+            .iter()
+            .find(|archetype| archetype.name == name)
+    }
+}
+
+#[derive(Debug, Reflect, Clone, Deserialize)]
+pub struct CustomerArchetype {
+    pub name: String,
+    /// How often this archetype shows up compared to
+    /// the others
+    pub weight: f32,
+    /// Names of `ItemCatalog` garments this customer
+    /// brings in, with how likely each one is. Leave
+    /// empty for any garment.
+    #[serde(default)]
+    pub items: Vec<(String, f32)>,
+    pub min_items: usize,
+    /// Treated as `min_items` if it's lower
+    pub max_items: usize,
+    /// Chance the customer orders each optional
+    /// pipeline stage
+    #[serde(default)]
+    pub optional_stage_chance: f64,
+    /// Chance something is left in the pockets of each
+    /// item that has them
+    #[serde(default)]
+    pub pocket_chance: f64,
+    /// How likely the customer is to run the machines
+    /// themselves instead of handing their laundry
+    /// over the counter
+    #[serde(default)]
+    pub self_service_chance: f64,
+    /// Which `ServiceTier`s the customer asks for, with
+    /// how likely each one is. Leave empty for
+    /// standard service only.
+    #[serde(default)]
+    pub tiers: Vec<(ServiceTier, f32)>,
+    /// in seconds
+    pub patience: f32,
+    /// Multiplier on the `base_price` of their items
+    pub payment: f32,
+    /// Top speed, they slow down to arrive and to
+    /// step around other people
+    pub walk_speed: f32,
+    /// How much the customer can carry
+    pub max_volume: u32,
+    /// Keys into `PlayerAssets::character_gltfs`. Leave
+    /// empty for any character.
+    #[serde(default)]
+    pub models: Vec<String>,
+}
+
+impl CustomerArchetype {
+    /// Picks the garments this customer brings in.
+    /// The same kind of garment can show up more than
+    /// once.
+    pub fn choose_items<'a>(
+        &self,
+        item_catalog: &'a ItemCatalog,
+        rng: &mut impl Rng,
+    ) -> Vec<&'a ItemDefinition> {
+        let max_items =
+            self.max_items.max(self.min_items);
+        let count =
+            rng.gen_range(self.min_items..=max_items);
+        (0..count)
+            .filter_map(|_| {
+                if self.items.is_empty() {
+                    return item_catalog
+                        .garments()
+                        .choose(&mut *rng);
+                }
+                let (name, _) = self
+                    .items
+                    .choose_weighted(
+                        &mut *rng,
+                        |(_, weight)| *weight,
+                    )
+                    .ok()?;
+                let definition = item_catalog.get(name);
+                if definition.is_none() {
+                    warn!(
+                        name,
+                        "archetype item not in catalog"
+                    );
+                }
+                definition.filter(|definition| {
+                    definition.washable
+                })
+            })
+            .collect()
+    }
+
+    /// The chances loaded from data, by field name
+    fn chances_mut(
+        &mut self,
+    ) -> [(&'static str, &mut f64); 3] {
+        [
+            (
+                "optional_stage_chance",
+                &mut self.optional_stage_chance,
+            ),
+            ("pocket_chance", &mut self.pocket_chance),
+            (
+                "self_service_chance",
+                &mut self.self_service_chance,
+            ),
+        ]
+    }
+
+    /// Picks how soon the customer wants their laundry
+    /// back
+    pub fn choose_tier(
+        &self,
+        rng: &mut impl Rng,
+    ) -> ServiceTier {
+        self.tiers
+            .choose_weighted(rng, |(_, weight)| *weight)
+            .map(|(tier, _)| *tier)
+            .unwrap_or_default()
+    }
+}
+
+fn is_chance(chance: f64) -> bool {
+    (0. ..=1.).contains(&chance)
+}
+
+/// Rolling a chance outside of 0..=1 panics, so
+/// typos in the data get clamped as soon as it loads.
+fn clamp_chances(
+    mut events: EventReader<AssetEvent<CustomerArchetypes>>,
+    mut assets: ResMut<Assets<CustomerArchetypes>>,
+) {
+    for event in events.read() {
+        let (AssetEvent::Added { id }
+        | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        // only borrow mutably when something needs
+        // fixing, that sends another `Modified`
+        let all_valid =
+            assets.get(*id).map_or(true, |data| {
+                data.archetypes.iter().all(|archetype| {
+                    [
+                        archetype.optional_stage_chance,
+                        archetype.pocket_chance,
+                        archetype.self_service_chance,
+                    ]
+                    .into_iter()
+                    .all(is_chance)
+                })
+            });
+        if all_valid {
+            continue;
+        }
+        let Some(data) = assets.get_mut(*id) else {
+            continue;
+        };
+        for archetype in &mut data.archetypes {
+            let name = archetype.name.clone();
+            for (field, chance) in archetype.chances_mut() {
+                if is_chance(*chance) {
+                    continue;
+                }
+                warn!(
+                    archetype = name,
+                    field,
+                    chance = *chance,
+                    "chance out of range"
+                );
+                *chance = if chance.is_nan() {
+                    0.
+                } else {
+                    chance.clamp(0., 1.)
+                };
+            }
+        }
+    }
+}
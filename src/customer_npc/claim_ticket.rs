@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+
+use super::{
+    join_shortest_queue, sample_spawn_point, spawn_customer,
+    CounterQueue, CustomerArchetypes, CustomerNpc,
+    CustomerNpcSpawner, JoinQueueSystems, Patience,
+    QueuedAt, SpawnerWeight, WaitingForStuffBack,
+};
+use crate::{
+    assets::GameDataAssets,
+    clock::GameClock,
+    persistent_id::PersistentId,
+    rng::{GameRng, RngStream},
+    states::{AppState, IsPaused},
+};
+
+pub struct ClaimTicketPlugin;
+
+impl Plugin for ClaimTicketPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ClaimTicket>().add_systems(
+            Update,
+            return_for_laundry
+                .in_set(JoinQueueSystems)
+                .run_if(in_state(IsPaused::Running)),
+        );
+    }
+}
+
+/// Handed to a customer that dropped off their
+/// laundry. They walk out and come back for it once
+/// the clock reaches `ready_at` and the shop is open.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct ClaimTicket {
+    pub customer_id: PersistentId,
+    /// The `CustomerArchetype` of the customer
+    pub archetype: String,
+    pub gltf: Handle<Gltf>,
+    /// The `GameClock::elapsed_minutes` the laundry
+    /// was promised for
+    pub ready_at: f32,
+    /// `Patience::waited` when they walked out
+    pub waited: f32,
+}
+
+/// Writes a ticket for `customer` to come back at
+/// `ready_at`, the deadline of their `Order`.
+pub(super) fn issue_claim_ticket(
+    commands: &mut Commands,
+    customer: &CustomerNpc,
+    customer_id: &PersistentId,
+    ready_at: f32,
+    waited: f32,
+) {
+    info!(
+        archetype = customer.archetype,
+        ready_at, "claim ticket issued"
+    );
+    commands.spawn((
+        Name::new(format!(
+            "ClaimTicket: {}",
+            customer.archetype
+        )),
+        ClaimTicket {
+            customer_id: customer_id.clone(),
+            archetype: customer.archetype.clone(),
+            gltf: customer.gltf.clone(),
+            ready_at,
+            waited,
+        },
+        StateScoped(AppState::InGame),
+    ));
+}
+
+/// Brings customers back through a spawner once their
+/// ticket is due. They line up again like everyone
+/// else.
+fn return_for_laundry(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    tickets: Query<(Entity, &ClaimTicket)>,
+    spawners: Query<
+        (
+            &Handle<Mesh>,
+            &GlobalTransform,
+            Option<&SpawnerWeight>,
+        ),
+        With<CustomerNpcSpawner>,
+    >,
+    meshes: Res<Assets<Mesh>>,
+    gltfs: Res<Assets<Gltf>>,
+    mut queues: Query<(Entity, &mut CounterQueue)>,
+    game_data: Res<GameDataAssets>,
+    customer_archetypes: Res<Assets<CustomerArchetypes>>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let Some(customer_archetypes) = customer_archetypes
+        .get(&game_data.customer_archetypes)
+    else {
+        return;
+    };
+
+    // nobody comes back to a locked door
+    if !clock.is_open() {
+        return;
+    }
+    let now = clock.elapsed_minutes();
+    for (ticket_entity, ticket) in &tickets {
+        if ticket.ready_at > now {
+            continue;
+        }
+        let Some(archetype) =
+            customer_archetypes.get(&ticket.archetype)
+        else {
+            warn!(
+                archetype = ticket.archetype,
+                "ticket for an unknown archetype"
+            );
+            commands.entity(ticket_entity).despawn();
+            continue;
+        };
+        let Some(spawn_point) = sample_spawn_point(
+            &spawners,
+            &meshes,
+            game_rng.stream(RngStream::Spawning),
+        ) else {
+            continue;
+        };
+        let Some(customer) = spawn_customer(
+            &mut commands,
+            archetype,
+            &ticket.gltf,
+            &gltfs,
+            Transform::from_translation(spawn_point),
+            ticket.customer_id.clone(),
+        ) else {
+            continue;
+        };
+        commands.entity(customer).insert(Patience {
+            waited: ticket.waited,
+            ..Patience::new(archetype.patience)
+        });
+        // the queue sends the customer to their place
+        // in line
+        if let Some(counter) =
+            join_shortest_queue(customer, &mut queues)
+        {
+            commands.entity(customer).insert(QueuedAt {
+                counter,
+                slot: None,
+            });
+        }
+
+        info!(
+            ?customer,
+            "customer is back for their laundry"
+        );
+        commands
+            .entity(customer)
+            .insert(WaitingForStuffBack);
+        commands.entity(ticket_entity).despawn();
+    }
+}
@@ -0,0 +1,397 @@
+use bevy::prelude::*;
+use rand::seq::IteratorRandom;
+use serde::Deserialize;
+
+use super::{
+    join_shortest_queue, sample_spawn_point,
+    spawn_customer, ClaimTicket, CounterQueue,
+    CustomerArchetypes, CustomerNpc, CustomerNpcSpawner,
+    CustomerServed, CustomerWalkedOut, JoinQueueSystems,
+    Order, OrderSettled, QueuedAt, ServiceTier,
+    SpawnerWeight,
+};
+use crate::{
+    assets::{GameDataAssets, PlayerAssets},
+    clock::{GameClock, MINUTES_PER_DAY},
+    inventory::{HeldBy, Item},
+    item_catalog::ItemCatalog,
+    persistent_id::PersistentId,
+    processing::LaundryPipeline,
+    rng::{GameRng, RngStream},
+    states::{AppState, IsPaused},
+};
+
+pub struct ContractPlugin;
+
+impl Plugin for ContractPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Contract>()
+            .add_event::<ContractEnded>()
+            .observe(accept_contract)
+            .add_systems(
+                Update,
+                (
+                    deliver_batches
+                        .in_set(JoinQueueSystems),
+                    settle_deliveries,
+                )
+                    .run_if(in_state(IsPaused::Running)),
+            );
+    }
+}
+
+/// Standing agreements businesses are willing to sign.
+///
+/// Loaded from `assets/data/business.contracts.ron`.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct ContractOffers {
+    pub offers: Vec<ContractTerms>,
+}
+
+#[derive(Debug, Reflect, Clone, Deserialize)]
+pub struct ContractTerms {
+    pub business: String,
+    /// The `CustomerArchetype` that drops off and
+    /// picks up every batch
+    pub courier: String,
+    /// Name of the `ItemCatalog` garment in every batch
+    pub item: String,
+    /// Items in every batch
+    pub batch_size: usize,
+    /// How many daily batches the agreement covers
+    pub days: u32,
+    /// Hour of the day the courier drops a batch off
+    pub delivery_hour: f32,
+    /// How soon every batch has to be ready
+    pub tier: ServiceTier,
+    /// Lowest average condition a batch can come back
+    /// in without breaching the contract
+    pub min_condition: f32,
+    /// Late, shabby or missed batches the business
+    /// puts up with before walking away
+    pub max_breaches: u32,
+}
+
+/// An accepted contract. Its `PersistentId` owns
+/// every item the business drops off, no matter which
+/// courier carried them in.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct Contract {
+    pub terms: ContractTerms,
+    /// Batches still to be dropped off
+    pub deliveries_left: u32,
+    /// The `GameClock::elapsed_minutes` of the next
+    /// drop off
+    pub next_delivery: f32,
+    pub breaches: u32,
+}
+
+/// Sign the `ContractOffers` entry at `offer`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct AcceptContract {
+    pub offer: usize,
+}
+
+/// A contract ran its course or the business walked
+/// away from it.
+#[derive(Event, Debug, Clone)]
+pub struct ContractEnded {
+    pub business: String,
+    pub completed: bool,
+}
+
+fn accept_contract(
+    trigger: Trigger<AcceptContract>,
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    game_data: Res<GameDataAssets>,
+    contract_offers: Res<Assets<ContractOffers>>,
+    contracts: Query<&Contract>,
+) {
+    let Some(terms) = contract_offers
+        .get(&game_data.contract_offers)
+        .and_then(|offers| {
+            offers.offers.get(trigger.event().offer)
+        })
+    else {
+        warn!(
+            offer = trigger.event().offer,
+            "no such offer"
+        );
+        return;
+    };
+    if contracts.iter().any(|contract| {
+        contract.terms.business == terms.business
+    }) {
+        warn!(
+            business = terms.business,
+            "already under contract"
+        );
+        return;
+    }
+
+    // the first batch comes in at the next delivery
+    // hour, which may be tomorrow
+    let now = clock.elapsed_minutes();
+    let mut next_delivery = clock.day as f32
+        * MINUTES_PER_DAY
+        + terms.delivery_hour * 60.;
+    if next_delivery < now {
+        next_delivery += MINUTES_PER_DAY;
+    }
+    info!(
+        business = terms.business,
+        next_delivery, "contract accepted"
+    );
+    commands.spawn((
+        Name::new(format!("Contract: {}", terms.business)),
+        Contract {
+            terms: terms.clone(),
+            deliveries_left: terms.days,
+            next_delivery,
+            breaches: 0,
+        },
+        PersistentId::new(),
+        StateScoped(AppState::InGame),
+    ));
+}
+
+/// Sends a courier in with a fresh batch every day
+/// at the agreed hour.
+fn deliver_batches(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut contracts: Query<(&mut Contract, &PersistentId)>,
+    open_batches: Query<&Order>,
+    tickets: Query<&ClaimTicket>,
+    couriers: Query<&PersistentId, With<CustomerNpc>>,
+    spawners: Query<
+        (
+            &Handle<Mesh>,
+            &GlobalTransform,
+            Option<&SpawnerWeight>,
+        ),
+        With<CustomerNpcSpawner>,
+    >,
+    meshes: Res<Assets<Mesh>>,
+    gltfs: Res<Assets<Gltf>>,
+    mut queues: Query<(Entity, &mut CounterQueue)>,
+    player_assets: Res<PlayerAssets>,
+    pipeline: Res<LaundryPipeline>,
+    game_data: Res<GameDataAssets>,
+    item_catalogs: Res<Assets<ItemCatalog>>,
+    customer_archetypes: Res<Assets<CustomerArchetypes>>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let (Some(item_catalog), Some(customer_archetypes)) = (
+        item_catalogs.get(&game_data.item_catalog),
+        customer_archetypes
+            .get(&game_data.customer_archetypes),
+    ) else {
+        return;
+    };
+
+    let now = clock.elapsed_minutes();
+    for (mut contract, contract_id) in &mut contracts {
+        if contract.deliveries_left == 0
+            || contract.next_delivery > now
+        {
+            continue;
+        }
+        contract.next_delivery += MINUTES_PER_DAY;
+        contract.deliveries_left -= 1;
+
+        // yesterday's batch is still in the shop, the
+        // business takes today's somewhere else
+        let batch_outstanding = open_batches
+            .iter()
+            .map(|order| &order.customer_id)
+            .chain(couriers.iter())
+            .chain(
+                tickets
+                    .iter()
+                    .map(|ticket| &ticket.customer_id),
+            )
+            .any(|id| id == contract_id);
+        if batch_outstanding {
+            warn!(
+                business = contract.terms.business,
+                "last batch still out, delivery missed"
+            );
+            contract.breaches += 1;
+            continue;
+        }
+
+        let terms = &contract.terms;
+        let (Some(archetype), Some(definition)) = (
+            customer_archetypes.get(&terms.courier),
+            item_catalog.get(&terms.item),
+        ) else {
+            warn!(
+                business = terms.business,
+                courier = terms.courier,
+                item = terms.item,
+                "contract courier or item is missing"
+            );
+            continue;
+        };
+        let Some(spawn_point) = sample_spawn_point(
+            &spawners,
+            &meshes,
+            game_rng.stream(RngStream::Spawning),
+        ) else {
+            continue;
+        };
+        let rng = game_rng.stream(RngStream::Customers);
+        let Some(gltf) = player_assets
+            .character_gltfs
+            .iter()
+            .filter(|(key, _)| {
+                archetype.models.is_empty()
+                    || archetype.models.contains(*key)
+            })
+            .map(|(_, gltf)| gltf)
+            .choose(&mut *rng)
+        else {
+            warn!(
+                archetype = archetype.name,
+                "no matching character models"
+            );
+            continue;
+        };
+        // the courier carries the contract's id so the
+        // batch goes back to whoever picks it up
+        let Some(courier) = spawn_customer(
+            &mut commands,
+            archetype,
+            gltf,
+            &gltfs,
+            Transform::from_translation(spawn_point),
+            contract_id.clone(),
+        ) else {
+            continue;
+        };
+        if let Some(counter) =
+            join_shortest_queue(courier, &mut queues)
+        {
+            commands.entity(courier).insert((
+                QueuedAt {
+                    counter,
+                    slot: None,
+                },
+                terms.tier,
+            ));
+        }
+
+        info!(
+            business = terms.business,
+            batch_size = terms.batch_size,
+            "contract batch delivered"
+        );
+        let order =
+            pipeline.order(&definition.stages, |_| false);
+        for _ in 0..terms.batch_size {
+            commands.spawn((
+                Name::new(format!(
+                    "Item: {}",
+                    definition.name
+                )),
+                Item::new(
+                    definition,
+                    Some(contract_id.clone()),
+                    order.clone(),
+                    definition.random_color(&mut *rng),
+                ),
+                HeldBy(courier),
+            ));
+        }
+    }
+}
+
+/// Holds every returned batch up against the terms and
+/// ends contracts that ran out or were breached too
+/// often.
+fn settle_deliveries(
+    mut commands: Commands,
+    mut contracts: Query<(
+        Entity,
+        &mut Contract,
+        &PersistentId,
+    )>,
+    mut served: EventReader<CustomerServed>,
+    mut settled: EventReader<OrderSettled>,
+    mut walked_out: EventReader<CustomerWalkedOut>,
+    mut ended: EventWriter<ContractEnded>,
+) {
+    // (owner, whether the batch is back, breached)
+    let outcomes = served
+        .read()
+        .map(|event| {
+            (
+                &event.customer_id,
+                true,
+                Some(event.average_condition),
+            )
+        })
+        .chain(
+            settled.read().filter(|event| event.late).map(
+                |event| (&event.customer_id, false, None),
+            ),
+        )
+        .chain(
+            walked_out.read().map(|event| {
+                (&event.customer_id, true, None)
+            }),
+        )
+        .collect::<Vec<_>>();
+
+    for (entity, mut contract, contract_id) in
+        &mut contracts
+    {
+        let mut batch_returned = false;
+        for (_, returned, condition) in outcomes
+            .iter()
+            .filter(|(owner, ..)| *owner == contract_id)
+        {
+            batch_returned |= returned;
+            let breached = match condition {
+                Some(condition) => {
+                    *condition
+                        < contract.terms.min_condition
+                }
+                // late, or the courier gave up waiting
+                None => true,
+            };
+            if breached {
+                contract.breaches += 1;
+                warn!(
+                    business = contract.terms.business,
+                    breaches = contract.breaches,
+                    "contract terms breached"
+                );
+            }
+        }
+
+        let completed = if contract.breaches
+            > contract.terms.max_breaches
+        {
+            false
+        } else if batch_returned
+            && contract.deliveries_left == 0
+        {
+            true
+        } else {
+            continue;
+        };
+        info!(
+            business = contract.terms.business,
+            completed, "contract ended"
+        );
+        ended.send(ContractEnded {
+            business: contract.terms.business.clone(),
+            completed,
+        });
+        commands.entity(entity).despawn();
+    }
+}
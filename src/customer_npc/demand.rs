@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::clock::Weekday;
+
+pub struct DemandPlugin;
+
+impl Plugin for DemandPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<LevelDemand>()
+            .register_type::<DemandPeak>();
+    }
+}
+
+/// How many customers show up over the course of a
+/// day, for every level.
+///
+/// Loaded from `assets/data/customers.demand.ron`.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct DemandProfiles {
+    pub levels: Vec<LevelDemand>,
+}
+
+impl DemandProfiles {
+    /// The demand for `level`, or the `"default"`
+    /// profile if the level doesn't have its own
+    pub fn for_level(
+        &self,
+        level: &str,
+    ) -> Option<&LevelDemand> {
+        self.levels
+            .iter()
+            .find(|demand| demand.level == level)
+            .or_else(|| {
+                self.levels.iter().find(|demand| {
+                    demand.level == "default"
+                })
+            })
+    }
+}
+
+#[derive(Debug, Reflect, Clone, Deserialize)]
+pub struct LevelDemand {
+    pub level: String,
+    /// Customers per in-game hour outside of any peak
+    pub base_rate: f32,
+    /// Rushes and lulls throughout the day
+    pub peaks: Vec<DemandPeak>,
+    /// Multiplier on the whole curve for each day,
+    /// starting on Monday
+    pub weekdays: [f32; 7],
+}
+
+/// A bump in the demand curve, centered on `hour`.
+/// A negative `strength` is a lull.
+#[derive(Debug, Reflect, Clone, Deserialize)]
+pub struct DemandPeak {
+    pub hour: f32,
+    /// How many hours the rush lasts, roughly. Anything
+    /// shorter than a minute counts as a minute.
+    pub width: f32,
+    /// Extra customers per hour at the height of the
+    /// rush
+    pub strength: f32,
+}
+
+impl DemandPeak {
+    fn rate(&self, hour: f32) -> f32 {
+        let width = self.width.max(1. / 60.);
+        let distance = (hour - self.hour) / width;
+        self.strength * (-0.5 * distance * distance).exp()
+    }
+}
+
+impl LevelDemand {
+    /// Expected customers per in-game hour at `hour` on
+    /// `weekday`
+    pub fn rate(&self, weekday: Weekday, hour: f32) -> f32 {
+        let rate = self.base_rate
+            + self
+                .peaks
+                .iter()
+                .map(|peak| peak.rate(hour))
+                .sum::<f32>();
+        (rate * self.weekdays[weekday.index()]).max(0.)
+    }
+
+    /// How many customers are expected to arrive on
+    /// `weekday` between `from_hour` and `to_hour`
+    pub fn expected_arrivals(
+        &self,
+        weekday: Weekday,
+        from_hour: f32,
+        to_hour: f32,
+    ) -> f32 {
+        // one step per in-game minute
+        const STEP: f32 = 1. / 60.;
+        let mut arrivals = 0.;
+        let mut hour = from_hour;
+        while hour < to_hour {
+            let step = STEP.min(to_hour - hour);
+            arrivals +=
+                self.rate(weekday, hour + step / 2.) * step;
+            hour += step;
+        }
+        arrivals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn demand(
+        base_rate: f32,
+        peaks: Vec<DemandPeak>,
+    ) -> LevelDemand {
+        LevelDemand {
+            level: "test".to_string(),
+            base_rate,
+            peaks,
+            weekdays: [1.; 7],
+        }
+    }
+
+    #[test]
+    fn flat_rate_over_opening_hours() {
+        let demand = demand(6., vec![]);
+        let arrivals = demand.expected_arrivals(
+            Weekday::Monday,
+            8.,
+            18.,
+        );
+        assert!((arrivals - 60.).abs() < 0.01);
+    }
+
+    #[test]
+    fn peak_adds_its_area_over_a_day() {
+        let demand = demand(
+            0.,
+            vec![DemandPeak {
+                hour: 12.,
+                width: 1.,
+                strength: 10.,
+            }],
+        );
+        assert!(
+            (demand.rate(Weekday::Monday, 12.) - 10.).abs()
+                < 0.001
+        );
+        let arrivals = demand.expected_arrivals(
+            Weekday::Monday,
+            0.,
+            24.,
+        );
+        let area = 10. * (2. * std::f32::consts::PI).sqrt();
+        assert!((arrivals - area).abs() < 0.01);
+    }
+
+    #[test]
+    fn weekday_multiplier_scales_the_curve() {
+        let mut demand = demand(4., vec![]);
+        demand.weekdays[Weekday::Sunday.index()] = 0.5;
+        assert_eq!(demand.rate(Weekday::Sunday, 10.), 2.);
+        assert_eq!(demand.rate(Weekday::Monday, 10.), 4.);
+    }
+
+    #[test]
+    fn lulls_never_go_below_zero() {
+        let demand = demand(
+            1.,
+            vec![DemandPeak {
+                hour: 15.,
+                width: 2.,
+                strength: -5.,
+            }],
+        );
+        assert_eq!(demand.rate(Weekday::Monday, 15.), 0.);
+    }
+
+    #[test]
+    fn zero_width_peak_stays_finite() {
+        let demand = demand(
+            1.,
+            vec![DemandPeak {
+                hour: 9.,
+                width: 0.,
+                strength: 3.,
+            }],
+        );
+        for hour in [8., 9., 10.] {
+            let rate = demand.rate(Weekday::Monday, hour);
+            assert!(rate.is_finite());
+        }
+        let arrivals = demand.expected_arrivals(
+            Weekday::Monday,
+            0.,
+            24.,
+        );
+        assert!(arrivals.is_finite());
+    }
+}
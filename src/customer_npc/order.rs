@@ -0,0 +1,288 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::{
+    CustomerNpc, CustomerServed, CustomerWalkedOut,
+    Satisfaction,
+};
+use crate::{
+    clock::GameClock,
+    inventory::Item,
+    persistent_id::PersistentId,
+    states::{AppState, IsPaused},
+};
+
+pub struct OrderPlugin;
+
+impl Plugin for OrderPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ServiceTier>()
+            .register_type::<Order>()
+            .add_event::<OrderSettled>()
+            .add_systems(
+                Update,
+                (track_order_progress, settle_orders)
+                    .run_if(in_state(IsPaused::Running)),
+            );
+    }
+}
+
+/// How soon a customer wants their laundry back, and
+/// how much more they'll pay for it.
+#[derive(
+    Component,
+    Debug,
+    Reflect,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    Deserialize,
+)]
+#[reflect(Component)]
+pub enum ServiceTier {
+    #[default]
+    Standard,
+    Express,
+    SameHour,
+}
+
+impl ServiceTier {
+    /// In-game minutes the shop promises to turn
+    /// `item_count` items around in
+    pub fn turnaround(&self, item_count: usize) -> f32 {
+        let items = item_count as f32;
+        match self {
+            ServiceTier::Standard => 90. + 15. * items,
+            ServiceTier::Express => 45. + 8. * items,
+            ServiceTier::SameHour => 60.,
+        }
+    }
+
+    /// Multiplier on the price of the order
+    pub fn price_multiplier(&self) -> f32 {
+        match self {
+            ServiceTier::Standard => 1.,
+            ServiceTier::Express => 1.5,
+            ServiceTier::SameHour => 2.,
+        }
+    }
+
+    /// Extra pay, as a fraction of the price, for
+    /// having the order ready by the deadline
+    pub fn on_time_bonus(&self) -> f32 {
+        match self {
+            ServiceTier::Standard => 0.,
+            ServiceTier::Express => 0.2,
+            ServiceTier::SameHour => 0.3,
+        }
+    }
+
+    /// Pay lost, as a fraction of the price, for
+    /// missing the deadline
+    pub fn late_penalty(&self) -> f32 {
+        match self {
+            ServiceTier::Standard => 0.1,
+            ServiceTier::Express => 0.3,
+            ServiceTier::SameHour => 0.5,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ServiceTier::Standard => "Standard",
+            ServiceTier::Express => "Express",
+            ServiceTier::SameHour => "Same Hour",
+        }
+    }
+}
+
+/// Laundry a customer left at the counter, promised
+/// back by `deadline`.
+///
+/// Times are `GameClock::elapsed_minutes`.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct Order {
+    pub customer_id: PersistentId,
+    /// The `CustomerArchetype` of the customer
+    pub archetype: String,
+    pub tier: ServiceTier,
+    pub item_count: usize,
+    /// in cents, the `base_price` of every item times
+    /// the customer's `payment`, before the tier
+    pub price: u32,
+    pub placed_at: f32,
+    pub deadline: f32,
+    /// When the last item was done, if it is
+    pub finished_at: Option<f32>,
+}
+
+impl Order {
+    pub fn is_late(&self, now: f32) -> bool {
+        self.finished_at.unwrap_or(now) > self.deadline
+    }
+
+    /// What the customer pays on pickup, as a
+    /// multiplier on the price of their items
+    pub fn pay_multiplier(&self, now: f32) -> f32 {
+        let adjustment = if self.is_late(now) {
+            -self.tier.late_penalty()
+        } else {
+            self.tier.on_time_bonus()
+        };
+        self.tier.price_multiplier() * (1. + adjustment)
+    }
+
+    /// What the customer pays on pickup, in cents
+    pub fn amount_due(&self, now: f32) -> u32 {
+        (self.price as f32 * self.pay_multiplier(now))
+            .round() as u32
+    }
+}
+
+/// A customer picked up their order. Sent after
+/// `CustomerServed`.
+#[derive(Event, Debug, Clone)]
+pub struct OrderSettled {
+    pub customer_id: PersistentId,
+    pub tier: ServiceTier,
+    pub late: bool,
+    pub pay_multiplier: f32,
+    /// in cents, what the customer paid for the order
+    pub amount: u32,
+    pub satisfaction: Satisfaction,
+}
+
+/// Opens an order for the `items` that were just
+/// handed over and returns its deadline.
+pub(super) fn place_order(
+    commands: &mut Commands,
+    customer: &CustomerNpc,
+    customer_id: &PersistentId,
+    tier: ServiceTier,
+    items: &[&Item],
+    clock: &GameClock,
+) -> f32 {
+    let item_count = items.len();
+    let price = (items
+        .iter()
+        .map(|item| item.base_price)
+        .sum::<u32>() as f32
+        * customer.payment)
+        .round() as u32;
+    let placed_at = clock.elapsed_minutes();
+    // the turnaround only counts opening hours
+    let deadline = clock
+        .open_minutes_after(tier.turnaround(item_count));
+    info!(
+        archetype = customer.archetype,
+        ?tier,
+        item_count,
+        price,
+        deadline,
+        "order placed"
+    );
+    commands.spawn((
+        Name::new(format!("Order: {}", customer.archetype)),
+        Order {
+            customer_id: customer_id.clone(),
+            archetype: customer.archetype.clone(),
+            tier,
+            item_count,
+            price,
+            placed_at,
+            deadline,
+            finished_at: None,
+        },
+        StateScoped(AppState::InGame),
+    ));
+    deadline
+}
+
+/// Notes the time an order's last item was done,
+/// which is what counts against the deadline.
+fn track_order_progress(
+    clock: Res<GameClock>,
+    mut orders: Query<&mut Order>,
+    items: Query<&Item>,
+) {
+    for mut order in &mut orders {
+        if order.finished_at.is_some() {
+            continue;
+        }
+        let mut owned = items
+            .iter()
+            .filter(|item| {
+                item.owner.as_ref()
+                    == Some(&order.customer_id)
+            })
+            .peekable();
+        if owned.peek().is_none()
+            || !owned.all(|item| item.is_finished())
+        {
+            continue;
+        }
+
+        let now = clock.elapsed_minutes();
+        info!(
+            archetype = order.archetype,
+            late = order.is_late(now),
+            "order ready"
+        );
+        order.finished_at = Some(now);
+    }
+}
+
+fn settle_orders(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    orders: Query<(Entity, &Order)>,
+    mut served: EventReader<CustomerServed>,
+    mut walked_out: EventReader<CustomerWalkedOut>,
+    mut settled: EventWriter<OrderSettled>,
+) {
+    let now = clock.elapsed_minutes();
+    for event in served.read() {
+        let Some((entity, order)) =
+            orders.iter().find(|(_, order)| {
+                order.customer_id == event.customer_id
+            })
+        else {
+            continue;
+        };
+        let late = order.is_late(now);
+        let pay_multiplier = order.pay_multiplier(now);
+        let amount = order.amount_due(now);
+        info!(
+            archetype = order.archetype,
+            tier = ?order.tier,
+            late,
+            amount,
+            "order settled"
+        );
+        settled.send(OrderSettled {
+            customer_id: order.customer_id.clone(),
+            tier: order.tier,
+            late,
+            pay_multiplier,
+            amount,
+            satisfaction: event.satisfaction,
+        });
+        commands.entity(entity).despawn();
+    }
+
+    // customers that walk out take their laundry with
+    // them, done or not
+    for event in walked_out.read() {
+        for (entity, _) in
+            orders.iter().filter(|(_, order)| {
+                order.customer_id == event.customer_id
+            })
+        {
+            info!(?entity, "order abandoned");
+            commands.entity(entity).despawn();
+        }
+    }
+}
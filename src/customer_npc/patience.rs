@@ -0,0 +1,263 @@
+use bevy::prelude::*;
+
+use super::{
+    leave_shop, play_customer_animation, CustomerNpc,
+    CustomerNpcAnimationNames, CustomerNpcSpawner, Leaving,
+    SelfService, WaitingForStuffBack,
+};
+use crate::{
+    clock::GameClock,
+    day_cycle::ClosingTime,
+    inventory::{HeldBy, Item},
+    navmesh::Path,
+    persistent_id::PersistentId,
+    states::IsPaused,
+};
+
+pub struct PatiencePlugin;
+
+impl Plugin for PatiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Patience>()
+            .register_type::<Mood>()
+            .add_event::<CustomerWalkedOut>()
+            .add_systems(
+                Update,
+                drain_patience
+                    .run_if(in_state(IsPaused::Running)),
+            )
+            .observe(refill_patience)
+            .observe(walk_out_at_closing);
+    }
+}
+
+/// How much longer a customer is willing to stand
+/// around. Only drains while they're waiting, not
+/// while they're walking.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct Patience {
+    pub remaining: Timer,
+    pub mood: Mood,
+    /// In-game minutes spent waiting so far, across
+    /// every visit
+    pub waited: f32,
+}
+
+impl Patience {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            remaining: Timer::from_seconds(
+                seconds,
+                TimerMode::Once,
+            ),
+            mood: Mood::Happy,
+            waited: 0.,
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Reflect,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum Mood {
+    Angry,
+    Impatient,
+    Happy,
+}
+
+impl Mood {
+    fn from_patience(patience: &Timer) -> Self {
+        match patience.fraction_remaining() {
+            f if f > 0.5 => Mood::Happy,
+            f if f > 0.2 => Mood::Impatient,
+            _ => Mood::Angry,
+        }
+    }
+}
+
+/// Sent when a customer ran out of patience and
+/// stormed out, gave up on the machines, or was still
+/// inside at midnight. Anything of theirs still in
+/// the shop goes with them.
+#[derive(Event, Debug, Clone)]
+pub struct CustomerWalkedOut {
+    pub customer: Entity,
+    pub customer_id: PersistentId,
+}
+
+fn drain_patience(
+    mut commands: Commands,
+    time: Res<Time>,
+    clock: Res<GameClock>,
+    mut customers: Query<
+        (
+            Entity,
+            &mut Patience,
+            &PersistentId,
+            Option<&SelfService>,
+        ),
+        (
+            With<CustomerNpc>,
+            Without<Leaving>,
+            Without<Path>,
+        ),
+    >,
+    items: Query<(Entity, &Item)>,
+    children: Query<&Children>,
+    mut animation_players: Query<(
+        &mut AnimationTransitions,
+        &mut AnimationPlayer,
+    )>,
+    spawner_meshes: Query<
+        (Entity, &Parent),
+        With<CustomerNpcSpawner>,
+    >,
+    transforms: Query<&Transform>,
+    mut walked_out: EventWriter<CustomerWalkedOut>,
+) {
+    for (
+        customer,
+        mut patience,
+        persistent_id,
+        self_service,
+    ) in &mut customers
+    {
+        // waiting on a machine of their own isn't
+        // waiting on the shop
+        if self_service.is_some_and(SelfService::is_busy) {
+            continue;
+        }
+        patience.remaining.tick(time.delta());
+        patience.waited += clock.minutes_in(time.delta());
+
+        let mood = Mood::from_patience(&patience.remaining);
+        if mood != patience.mood {
+            info!(?customer, ?mood, "customer mood");
+            play_customer_animation(
+                customer,
+                if mood > patience.mood {
+                    CustomerNpcAnimationNames::EmoteYes
+                } else {
+                    CustomerNpcAnimationNames::EmoteNo
+                },
+                &children,
+                &mut animation_players,
+            );
+            patience.mood = mood;
+        }
+
+        if !patience.remaining.finished() {
+            continue;
+        }
+
+        info!(?customer, "customer walked out");
+        walk_out(
+            &mut commands,
+            customer,
+            persistent_id,
+            &items,
+            &spawner_meshes,
+            &transforms,
+            &mut walked_out,
+        );
+    }
+}
+
+/// Nobody stays in the shop past midnight, however
+/// patient they are.
+fn walk_out_at_closing(
+    _trigger: Trigger<ClosingTime>,
+    mut commands: Commands,
+    customers: Query<
+        (Entity, &PersistentId),
+        (With<CustomerNpc>, Without<Leaving>),
+    >,
+    items: Query<(Entity, &Item)>,
+    spawner_meshes: Query<
+        (Entity, &Parent),
+        With<CustomerNpcSpawner>,
+    >,
+    transforms: Query<&Transform>,
+    mut walked_out: EventWriter<CustomerWalkedOut>,
+) {
+    for (customer, persistent_id) in &customers {
+        info!(?customer, "customer sent home at midnight");
+        walk_out(
+            &mut commands,
+            customer,
+            persistent_id,
+            &items,
+            &spawner_meshes,
+            &transforms,
+            &mut walked_out,
+        );
+    }
+}
+
+fn walk_out(
+    commands: &mut Commands,
+    customer: Entity,
+    persistent_id: &PersistentId,
+    items: &Query<(Entity, &Item)>,
+    spawner_meshes: &Query<
+        (Entity, &Parent),
+        With<CustomerNpcSpawner>,
+    >,
+    transforms: &Query<&Transform>,
+    walked_out: &mut EventWriter<CustomerWalkedOut>,
+) {
+    // the customer takes back whatever the shop still
+    // had of theirs, washed or not
+    for (item, _) in items.iter().filter(|(_, item)| {
+        item.owner.as_ref() == Some(persistent_id)
+    }) {
+        commands.entity(item).insert(HeldBy(customer));
+    }
+    leave_shop(
+        commands,
+        customer,
+        spawner_meshes,
+        transforms,
+    );
+    walked_out.send(CustomerWalkedOut {
+        customer,
+        customer_id: persistent_id.clone(),
+    });
+}
+
+/// Customers coming back with a claim ticket are
+/// willing to wait all over again to get their
+/// laundry back.
+fn refill_patience(
+    trigger: Trigger<OnAdd, WaitingForStuffBack>,
+    mut customers: Query<&mut Patience>,
+    children: Query<&Children>,
+    mut animation_players: Query<(
+        &mut AnimationTransitions,
+        &mut AnimationPlayer,
+    )>,
+) {
+    let Ok(mut patience) =
+        customers.get_mut(trigger.entity())
+    else {
+        return;
+    };
+    patience.remaining.reset();
+    if patience.mood != Mood::Happy {
+        play_customer_animation(
+            trigger.entity(),
+            CustomerNpcAnimationNames::EmoteYes,
+            &children,
+            &mut animation_players,
+        );
+    }
+    patience.mood = Mood::Happy;
+}
@@ -0,0 +1,180 @@
+use avian3d::prelude::CollidingEntities;
+use bevy::prelude::*;
+
+use super::{
+    CustomerDropoffLocation, CustomerNpcSpawner, Leaving,
+    PlayerReceiveFromCustomerLocation,
+};
+use crate::{
+    navmesh::{Destination, Object},
+    states::IsPaused,
+};
+
+pub struct QueuePlugin;
+
+impl Plugin for QueuePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<CounterQueue>()
+            .register_type::<QueuedAt>()
+            .add_systems(
+                Update,
+                // customers spawned into a line need to
+                // exist before the line is tidied up
+                update_queues
+                    .after(JoinQueueSystems)
+                    .run_if(in_state(IsPaused::Running)),
+            )
+            .observe(add_counter_queue);
+    }
+}
+
+/// Systems that spawn customers straight into a
+/// `CounterQueue` run in this set.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JoinQueueSystems;
+
+/// How far apart customers stand in line
+const QUEUE_SPACING: f32 = 1.5;
+
+/// The customers lined up at a
+/// `CustomerDropoffLocation`, front of the line
+/// first.
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct CounterQueue {
+    pub customers: Vec<Entity>,
+}
+
+/// Which counter a customer is lined up at, and
+/// where in line they are. Slot 0 is at the counter.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct QueuedAt {
+    pub counter: Entity,
+    /// `None` until the customer has been sent to
+    /// their place in line
+    pub slot: Option<usize>,
+}
+
+impl QueuedAt {
+    pub fn is_at_front(&self) -> bool {
+        self.slot == Some(0)
+    }
+}
+
+fn add_counter_queue(
+    trigger: Trigger<OnAdd, CustomerDropoffLocation>,
+    mut commands: Commands,
+) {
+    commands
+        .entity(trigger.entity())
+        .insert(CounterQueue::default());
+}
+
+/// Puts `customer` at the back of the shortest line
+/// and returns the counter they're lined up at.
+pub fn join_shortest_queue(
+    customer: Entity,
+    queues: &mut Query<(Entity, &mut CounterQueue)>,
+) -> Option<Entity> {
+    let (counter, mut queue) = queues
+        .iter_mut()
+        .min_by_key(|(_, queue)| queue.customers.len())?;
+    queue.customers.push(customer);
+    Some(counter)
+}
+
+/// The counter the player is serving from: the
+/// `CustomerDropoffLocation` closest to the
+/// `PlayerReceiveFromCustomerLocation` the player is
+/// standing in.
+pub fn staffed_counter(
+    player: Entity,
+    pickup_locations: &Query<
+        (&CollidingEntities, &GlobalTransform),
+        With<PlayerReceiveFromCustomerLocation>,
+    >,
+    counters: &Query<
+        (Entity, &CollidingEntities, &GlobalTransform),
+        With<CustomerDropoffLocation>,
+    >,
+) -> Option<Entity> {
+    let (_, pickup_transform) =
+        pickup_locations.iter().find(
+            |(colliding, _)| colliding.contains(&player),
+        )?;
+    counters
+        .iter()
+        .min_by(|(_, _, a), (_, _, b)| {
+            let a = a.translation().distance_squared(
+                pickup_transform.translation(),
+            );
+            let b = b.translation().distance_squared(
+                pickup_transform.translation(),
+            );
+            a.total_cmp(&b)
+        })
+        .map(|(counter, _, _)| counter)
+}
+
+/// Customers on their way out step out of line, and
+/// everyone behind them moves up.
+fn update_queues(
+    mut commands: Commands,
+    mut queues: Query<(
+        Entity,
+        &mut CounterQueue,
+        &GlobalTransform,
+    )>,
+    mut customers: Query<&mut QueuedAt, Without<Leaving>>,
+    spawner_meshes: Query<
+        &GlobalTransform,
+        With<CustomerNpcSpawner>,
+    >,
+) {
+    let entrance = spawner_meshes
+        .iter()
+        .next()
+        .map(|transform| transform.translation());
+
+    for (counter, mut queue, counter_transform) in
+        &mut queues
+    {
+        queue.customers.retain(|customer| {
+            customers.contains(*customer)
+        });
+
+        let front = counter_transform.translation();
+        // lines grow back towards the door
+        let behind = entrance
+            .map(|entrance| {
+                (entrance - front)
+                    .with_y(0.)
+                    .normalize_or_zero()
+            })
+            .unwrap_or(Vec3::Z);
+
+        for (slot, customer) in
+            queue.customers.iter().enumerate()
+        {
+            let Ok(mut queued_at) =
+                customers.get_mut(*customer)
+            else {
+                continue;
+            };
+            if queued_at.slot == Some(slot) {
+                continue;
+            }
+            queued_at.slot = Some(slot);
+            commands.entity(*customer).insert((
+                Object(Some(counter)),
+                Destination(
+                    front
+                        + behind
+                            * QUEUE_SPACING
+                            * slot as f32,
+                ),
+            ));
+        }
+    }
+}
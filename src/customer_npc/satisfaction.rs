@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+
+use super::CustomerWalkedOut;
+use crate::{inventory::Item, persistent_id::PersistentId};
+
+pub struct SatisfactionPlugin;
+
+impl Plugin for SatisfactionPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Satisfaction>()
+            .register_type::<Reputation>()
+            .init_resource::<Reputation>()
+            .add_event::<CustomerServed>()
+            .add_systems(Update, update_reputation);
+    }
+}
+
+/// How a customer feels about the laundry they got
+/// back.
+#[derive(
+    Debug,
+    Reflect,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum Satisfaction {
+    Unhappy,
+    Satisfied,
+    Delighted,
+}
+
+impl Satisfaction {
+    /// Better than it was when they dropped it off
+    const DELIGHTED_CONDITION: f32 = 0.85;
+    /// Roughly the way they dropped it off
+    const SATISFIED_CONDITION: f32 = 0.65;
+
+    pub fn from_condition(average_condition: f32) -> Self {
+        if average_condition >= Self::DELIGHTED_CONDITION {
+            Satisfaction::Delighted
+        } else if average_condition
+            >= Self::SATISFIED_CONDITION
+        {
+            Satisfaction::Satisfied
+        } else {
+            Satisfaction::Unhappy
+        }
+    }
+}
+
+/// The average condition of the washable items in
+/// `items`. Things that were never meant to be
+/// washed, like a pen from a pocket, don't count.
+pub fn average_condition<'a>(
+    items: impl IntoIterator<Item = &'a Item>,
+) -> f32 {
+    let (total, count) = items
+        .into_iter()
+        .filter(|item| item.washable)
+        .fold((0., 0), |(total, count), item| {
+            (total + item.condition, count + 1)
+        });
+    if count == 0 {
+        return Item::DROPOFF_CONDITION;
+    }
+    total / count as f32
+}
+
+/// Sent when a customer has all of their items back
+/// and is about to leave.
+#[derive(Event, Debug, Clone)]
+pub struct CustomerServed {
+    pub customer: Entity,
+    pub customer_id: PersistentId,
+    pub average_condition: f32,
+    pub satisfaction: Satisfaction,
+    /// In-game minutes the customer spent waiting on
+    /// the shop
+    pub waited: f32,
+}
+
+/// What the neighborhood thinks of the shop. Happy
+/// customers tell their friends, customers who
+/// stormed out tell everyone.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct Reputation(pub f32);
+
+impl Default for Reputation {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl Reputation {
+    const MIN: f32 = 0.25;
+    const MAX: f32 = 2.;
+
+    /// How many more (or fewer) customers than usual
+    /// come in
+    pub fn customer_rate(&self) -> f32 {
+        self.0
+    }
+
+    fn change(&mut self, amount: f32) {
+        self.0 =
+            (self.0 + amount).clamp(Self::MIN, Self::MAX);
+    }
+}
+
+fn update_reputation(
+    mut served: EventReader<CustomerServed>,
+    mut walked_out: EventReader<CustomerWalkedOut>,
+    mut reputation: ResMut<Reputation>,
+) {
+    for event in served.read() {
+        reputation.change(match event.satisfaction {
+            Satisfaction::Unhappy => -0.05,
+            Satisfaction::Satisfied => 0.02,
+            Satisfaction::Delighted => 0.05,
+        });
+    }
+    for _ in walked_out.read() {
+        reputation.change(-0.1);
+    }
+}
@@ -0,0 +1,550 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::{
+    average_condition, leave_shop, CustomerNpcSpawner,
+    CustomerServed, CustomerWalkedOut, Leaving, Patience,
+    Satisfaction,
+};
+use crate::{
+    game_scene::WashingMachine,
+    grid::{
+        Done, InUseBy, MachineKind, MaxLoad, Overloaded,
+        StartWork, Working,
+    },
+    inventory::{
+        HeldBy, InventoryTransfer, Item, ItemFilter,
+    },
+    navmesh::{Destination, Object, Path},
+    persistent_id::PersistentId,
+    processing::{MachineProgram, ProcessingStep},
+    rng::{GameRng, RngStream},
+    states::IsPaused,
+    utilities::{CycleUsage, DetergentStock},
+};
+
+pub struct SelfServicePlugin;
+
+impl Plugin for SelfServicePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SelfServiceCyclePaid>()
+            .add_systems(
+                Update,
+                (
+                    find_machine,
+                    load_machine,
+                    wait_for_machine,
+                    unload_machine,
+                )
+                    .run_if(in_state(IsPaused::Running)),
+            )
+            .observe(release_machines::<OnAdd, Leaving>)
+            .observe(
+                release_machines::<OnRemove, SelfService>,
+            );
+    }
+}
+
+/// How far from a machine customers stand to use it
+const MACHINE_REACH: f32 = 1.2;
+/// Customers waiting on a machine stroll around
+/// within this distance of it
+const WANDER_RADIUS: f32 = 3.;
+/// Seconds between strolls
+const WANDER_INTERVAL: f32 = 8.;
+
+/// A customer that runs the shop's machines
+/// themselves instead of handing their laundry over
+/// the counter. They compete with the player for
+/// free machines and pay for every cycle.
+#[derive(Component, Debug, Default)]
+pub enum SelfService {
+    /// Looking for a free machine for the next step
+    /// of their laundry
+    #[default]
+    LookingForMachine,
+    /// On their way to load a machine
+    GoingToMachine(Entity),
+    /// Hanging around while their machine runs
+    Waiting { machine: Entity, wander: Timer },
+    /// On their way to empty a finished machine
+    Collecting(Entity),
+}
+
+impl SelfService {
+    /// Customers only lose patience while they have
+    /// no machine to use
+    pub fn is_busy(&self) -> bool {
+        !matches!(self, SelfService::LookingForMachine)
+    }
+}
+
+/// A self-service customer paid to start a machine.
+#[derive(Event, Debug, Clone)]
+pub struct SelfServiceCyclePaid {
+    pub customer: Entity,
+    pub customer_id: PersistentId,
+    pub machine: Entity,
+    pub step: ProcessingStep,
+    /// in cents
+    pub price: u32,
+}
+
+/// A spot next to `machine`, on the side facing
+/// `from`.
+fn beside(machine: Vec3, from: Vec3) -> Vec3 {
+    let side =
+        (from - machine).with_y(0.).normalize_or_zero();
+    (machine + side * MACHINE_REACH).with_y(from.y)
+}
+
+fn within_reach(position: Vec3, machine: Vec3) -> bool {
+    position.xz().distance(machine.xz())
+        < MACHINE_REACH + 0.5
+}
+
+fn find_machine(
+    mut commands: Commands,
+    mut customers: Query<
+        (
+            Entity,
+            &Transform,
+            &PersistentId,
+            &Patience,
+            &mut SelfService,
+        ),
+        (Without<Path>, Without<Leaving>),
+    >,
+    machines: Query<
+        (Entity, &GlobalTransform, &MachineKind),
+        (
+            With<WashingMachine>,
+            Without<Working>,
+            Without<Done>,
+            Without<Overloaded>,
+            Without<InUseBy>,
+        ),
+    >,
+    items: Query<(&Item, &HeldBy)>,
+    spawner_meshes: Query<
+        (Entity, &Parent),
+        With<CustomerNpcSpawner>,
+    >,
+    transforms: Query<&Transform>,
+    mut served: EventWriter<CustomerServed>,
+) {
+    for (
+        customer,
+        transform,
+        customer_id,
+        patience,
+        mut self_service,
+    ) in &mut customers
+    {
+        if !matches!(
+            *self_service,
+            SelfService::LookingForMachine
+        ) {
+            continue;
+        }
+
+        // everything gets washed before anything is
+        // dried, and so on down the pipeline
+        let next_step =
+            ProcessingStep::ALL.into_iter().find(|step| {
+                items.iter().any(|(item, held_by)| {
+                    held_by.0 == customer
+                        && item.washable
+                        && item.next_step() == Some(*step)
+                })
+            });
+        let Some(next_step) = next_step else {
+            let average_condition = average_condition(
+                items
+                    .iter()
+                    .filter(|(_, held_by)| {
+                        held_by.0 == customer
+                    })
+                    .map(|(item, _)| item),
+            );
+            let satisfaction = Satisfaction::from_condition(
+                average_condition,
+            );
+            info!(
+                ?customer,
+                average_condition,
+                ?satisfaction,
+                "self-service customer done"
+            );
+            served.send(CustomerServed {
+                customer,
+                customer_id: customer_id.clone(),
+                average_condition,
+                satisfaction,
+                waited: patience.waited,
+            });
+            leave_shop(
+                &mut commands,
+                customer,
+                &spawner_meshes,
+                &transforms,
+            );
+            continue;
+        };
+
+        let position = transform.translation;
+        let Some((machine, machine_transform, _)) =
+            machines
+                .iter()
+                .filter(|(machine, _, kind)| {
+                    kind.0 == next_step
+                        && !items.iter().any(
+                            |(_, held_by)| {
+                                held_by.0 == *machine
+                            },
+                        )
+                })
+                .min_by(|(_, a, _), (_, b, _)| {
+                    a.translation()
+                        .distance_squared(position)
+                        .total_cmp(
+                            &b.translation()
+                                .distance_squared(position),
+                        )
+                })
+        else {
+            // nothing free, wait and lose patience
+            continue;
+        };
+
+        *self_service =
+            SelfService::GoingToMachine(machine);
+        commands.entity(customer).insert((
+            Object(Some(machine)),
+            Destination(beside(
+                machine_transform.translation(),
+                position,
+            )),
+        ));
+    }
+}
+
+fn load_machine(
+    mut commands: Commands,
+    mut customers: Query<
+        (
+            Entity,
+            &Transform,
+            &PersistentId,
+            &mut SelfService,
+        ),
+        (Without<Path>, Without<Leaving>),
+    >,
+    machines: Query<
+        (
+            &GlobalTransform,
+            &MachineKind,
+            &MaxLoad,
+            Has<Working>,
+            Has<Done>,
+            Has<Overloaded>,
+            Has<InUseBy>,
+            Option<(&CycleUsage, &MachineProgram)>,
+        ),
+        With<WashingMachine>,
+    >,
+    items: Query<(&Item, &HeldBy)>,
+    spawner_meshes: Query<
+        (Entity, &Parent),
+        With<CustomerNpcSpawner>,
+    >,
+    transforms: Query<&Transform>,
+    detergent: Res<DetergentStock>,
+    mut paid: EventWriter<SelfServiceCyclePaid>,
+    mut walked_out: EventWriter<CustomerWalkedOut>,
+) {
+    for (
+        customer,
+        transform,
+        customer_id,
+        mut self_service,
+    ) in &mut customers
+    {
+        let SelfService::GoingToMachine(machine) =
+            *self_service
+        else {
+            continue;
+        };
+        let Ok((
+            machine_transform,
+            kind,
+            max_load,
+            working,
+            done,
+            overloaded,
+            in_use,
+            usage,
+        )) = machines.get(machine)
+        else {
+            *self_service = SelfService::LookingForMachine;
+            continue;
+        };
+        if !within_reach(
+            transform.translation,
+            machine_transform.translation(),
+        ) {
+            continue;
+        }
+
+        let taken = working
+            || done
+            || overloaded
+            || in_use
+            || items
+                .iter()
+                .any(|(_, held_by)| held_by.0 == machine);
+        if taken {
+            info!(
+                ?customer,
+                ?machine,
+                "somebody got to the machine first"
+            );
+            *self_service = SelfService::LookingForMachine;
+            continue;
+        }
+
+        let filter = ItemFilter {
+            owner: Some(customer_id.clone()),
+            washable: Some(true),
+            next_step: Some(kind.0),
+            ..default()
+        };
+        let fits = items.iter().any(|(item, held_by)| {
+            held_by.0 == customer
+                && filter.matches(item)
+                && item.weight <= max_load.0
+        });
+        if !fits {
+            warn!(
+                ?customer,
+                ?machine,
+                "laundry is too heavy for the machine"
+            );
+            leave_shop(
+                &mut commands,
+                customer,
+                &spawner_meshes,
+                &transforms,
+            );
+            walked_out.send(CustomerWalkedOut {
+                customer,
+                customer_id: customer_id.clone(),
+            });
+            continue;
+        }
+
+        let out_of_detergent =
+            usage.is_some_and(|(usage, program)| {
+                let usage = usage.for_program(*program);
+                !detergent.covers(usage)
+            });
+        if out_of_detergent {
+            warn!(
+                ?customer,
+                ?machine,
+                "no detergent, customer gives up"
+            );
+            leave_shop(
+                &mut commands,
+                customer,
+                &spawner_meshes,
+                &transforms,
+            );
+            walked_out.send(CustomerWalkedOut {
+                customer,
+                customer_id: customer_id.clone(),
+            });
+            continue;
+        }
+
+        // only as much as the machine can take, the
+        // rest waits for another cycle
+        commands.trigger(InventoryTransfer {
+            from: customer,
+            to: machine,
+            filter,
+            all_or_nothing: false,
+            max_weight: Some(max_load.0),
+        });
+        commands.trigger_targets(StartWork, machine);
+        commands.entity(machine).insert(InUseBy(customer));
+
+        let price = kind.0.self_service_price();
+        info!(
+            ?customer,
+            ?machine,
+            price,
+            "machine started"
+        );
+        paid.send(SelfServiceCyclePaid {
+            customer,
+            customer_id: customer_id.clone(),
+            machine,
+            step: kind.0,
+            price,
+        });
+        *self_service = SelfService::Waiting {
+            machine,
+            wander: Timer::from_seconds(
+                WANDER_INTERVAL,
+                TimerMode::Repeating,
+            ),
+        };
+    }
+}
+
+fn wait_for_machine(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut customers: Query<
+        (Entity, &Transform, &mut SelfService, Has<Path>),
+        Without<Leaving>,
+    >,
+    machines: Query<
+        (&GlobalTransform, Has<Done>, Has<Overloaded>),
+        With<WashingMachine>,
+    >,
+    mut game_rng: ResMut<GameRng>,
+) {
+    for (customer, transform, mut self_service, walking) in
+        &mut customers
+    {
+        let SelfService::Waiting { machine, .. } =
+            *self_service
+        else {
+            continue;
+        };
+        let Ok((machine_transform, done, overloaded)) =
+            machines.get(machine)
+        else {
+            *self_service = SelfService::LookingForMachine;
+            continue;
+        };
+        let machine_position =
+            machine_transform.translation();
+
+        if done || overloaded {
+            *self_service =
+                SelfService::Collecting(machine);
+            commands.entity(customer).insert((
+                Object(Some(machine)),
+                Destination(beside(
+                    machine_position,
+                    transform.translation,
+                )),
+            ));
+            continue;
+        }
+
+        let SelfService::Waiting { wander, .. } =
+            &mut *self_service
+        else {
+            continue;
+        };
+        if walking
+            || !wander.tick(time.delta()).just_finished()
+        {
+            continue;
+        }
+        let rng = game_rng.stream(RngStream::Customers);
+        let angle = rng.gen_range(0.0..TAU);
+        let distance =
+            rng.gen_range(MACHINE_REACH..WANDER_RADIUS);
+        let stroll = machine_position
+            + Vec3::new(angle.cos(), 0., angle.sin())
+                * distance;
+        commands.entity(customer).insert((
+            Object(Some(machine)),
+            Destination(
+                stroll.with_y(transform.translation.y),
+            ),
+        ));
+    }
+}
+
+/// Frees the machine of a customer that walked out
+/// or is gone, whatever state it was left in. Their
+/// laundry has already gone with them.
+fn release_machines<E: Event, B: Bundle>(
+    trigger: Trigger<E, B>,
+    mut commands: Commands,
+    machines: Query<(Entity, &InUseBy)>,
+) {
+    for (machine, in_use_by) in &machines {
+        if in_use_by.0 != trigger.entity() {
+            continue;
+        }
+        info!(?machine, "machine released");
+        commands.entity(machine).remove::<(
+            InUseBy,
+            Working,
+            Done,
+            Overloaded,
+        )>();
+    }
+}
+
+fn unload_machine(
+    mut commands: Commands,
+    mut customers: Query<
+        (
+            Entity,
+            &Transform,
+            &PersistentId,
+            &mut SelfService,
+        ),
+        (Without<Path>, Without<Leaving>),
+    >,
+    machines: Query<&GlobalTransform, With<WashingMachine>>,
+) {
+    for (
+        customer,
+        transform,
+        customer_id,
+        mut self_service,
+    ) in &mut customers
+    {
+        let SelfService::Collecting(machine) =
+            *self_service
+        else {
+            continue;
+        };
+        let Ok(machine_transform) = machines.get(machine)
+        else {
+            *self_service = SelfService::LookingForMachine;
+            continue;
+        };
+        if !within_reach(
+            transform.translation,
+            machine_transform.translation(),
+        ) {
+            continue;
+        }
+
+        commands.trigger(InventoryTransfer {
+            from: machine,
+            to: customer,
+            filter: ItemFilter {
+                owner: Some(customer_id.clone()),
+                ..default()
+            },
+            all_or_nothing: false,
+            max_weight: None,
+        });
+        // `Done` comes off once the machine is empty
+        commands.entity(machine).remove::<InUseBy>();
+        *self_service = SelfService::LookingForMachine;
+    }
+}
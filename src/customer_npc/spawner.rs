@@ -0,0 +1,110 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::VertexAttributeValues,
+        render_resource::PrimitiveTopology,
+    },
+};
+use rand::{distributions::WeightedIndex, prelude::*};
+
+use super::CustomerNpcSpawner;
+
+pub struct SpawnerPlugin;
+
+impl Plugin for SpawnerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SpawnerWeight>();
+    }
+}
+
+/// How often customers come in through this
+/// `CustomerNpcSpawner` compared to the others.
+/// Spawners without one have a weight of 1.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct SpawnerWeight(pub f32);
+
+/// Picks a spawner by its weight, then a point on its
+/// mesh in world space.
+pub(super) fn sample_spawn_point(
+    spawners: &Query<
+        (
+            &Handle<Mesh>,
+            &GlobalTransform,
+            Option<&SpawnerWeight>,
+        ),
+        With<CustomerNpcSpawner>,
+    >,
+    meshes: &Assets<Mesh>,
+    rng: &mut impl Rng,
+) -> Option<Vec3> {
+    let spawners = spawners.iter().collect::<Vec<_>>();
+    let (mesh, transform, _) = spawners
+        .choose_weighted(&mut *rng, |(_, _, weight)| {
+            weight.map_or(1., |weight| weight.0)
+        })
+        .ok()?;
+    let Some(mesh) = meshes.get(*mesh) else {
+        warn!("spawner mesh is not loaded");
+        return None;
+    };
+    let point = sample_mesh_surface(mesh, &mut *rng)?;
+    Some(transform.transform_point(point))
+}
+
+/// A random point on the surface of `mesh`. Every spot
+/// is equally likely no matter how the mesh is
+/// triangulated.
+pub fn sample_mesh_surface(
+    mesh: &Mesh,
+    rng: &mut impl Rng,
+) -> Option<Vec3> {
+    if !matches!(
+        mesh.primitive_topology(),
+        PrimitiveTopology::TriangleList
+    ) {
+        warn!("spawner is not a TriangleList");
+        return None;
+    }
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        warn!("can't get ATTRIBUTE_POSITION from Mesh");
+        return None;
+    };
+
+    let corners = match mesh.indices() {
+        Some(indices) => indices
+            .iter()
+            .map(|index| positions.get(index).copied())
+            .collect::<Option<Vec<_>>>()?,
+        None => positions.clone(),
+    };
+    let triangles = corners
+        .chunks_exact(3)
+        .map(|corners| {
+            [corners[0], corners[1], corners[2]]
+                .map(Vec3::from)
+        })
+        .collect::<Vec<_>>();
+
+    // bigger triangles get picked more often
+    let areas = triangles.iter().map(|[a, b, c]| {
+        (*b - *a).cross(*c - *a).length() / 2.
+    });
+    let Ok(by_area) = WeightedIndex::new(areas) else {
+        warn!("spawner mesh has no area");
+        return None;
+    };
+    let [a, b, c] = triangles[by_area.sample(&mut *rng)];
+
+    // a point in the unit square, folded back into the
+    // triangle if it landed on the far half
+    let (mut u, mut v) =
+        (rng.r#gen::<f32>(), rng.r#gen::<f32>());
+    if u + v > 1. {
+        u = 1. - u;
+        v = 1. - v;
+    }
+    Some(a + (b - a) * u + (c - a) * v)
+}
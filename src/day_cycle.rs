@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+
+use crate::{
+    clock::{DayEnded, GameClock, MINUTES_PER_DAY},
+    customer_npc::{
+        CustomerNpc, CustomerServed, CustomerWalkedOut,
+        Leaving, Satisfaction,
+    },
+    states::{AppState, IsPaused},
+};
+
+pub struct DayCyclePlugin;
+
+impl Plugin for DayCyclePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<DayStats>()
+            .init_resource::<DayStats>()
+            .observe(start_next_day)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                reset_day,
+            )
+            .add_systems(
+                Update,
+                (track_customers, close_up)
+                    .chain()
+                    .run_if(in_state(IsPaused::Running)),
+            );
+    }
+}
+
+/// How the day went for the shop's customers.
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct DayStats {
+    pub customers_served: u32,
+    /// In-game minutes every served customer spent
+    /// waiting, added up
+    pub total_wait: f32,
+    /// Unhappy customers and customers that walked out
+    pub complaints: u32,
+}
+
+impl DayStats {
+    /// in in-game minutes
+    pub fn average_wait(&self) -> f32 {
+        if self.customers_served == 0 {
+            return 0.;
+        }
+        self.total_wait / self.customers_served as f32
+    }
+}
+
+/// Inserted once the shop has closed up for `day`.
+/// The game stays paused on the end of day summary
+/// until the player starts the next one.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct ClosedForTheDay {
+    pub day: u32,
+}
+
+/// Midnight came with customers still in the shop.
+/// They walk out, and the day ends once they're on
+/// their way.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ClosingTime;
+
+/// Leave the end of day summary and open the shop
+/// the next morning.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct StartNextDay;
+
+fn reset_day(mut commands: Commands) {
+    commands.insert_resource(DayStats::default());
+    commands.remove_resource::<ClosedForTheDay>();
+}
+
+fn track_customers(
+    mut served: EventReader<CustomerServed>,
+    mut walked_out: EventReader<CustomerWalkedOut>,
+    mut stats: ResMut<DayStats>,
+) {
+    for event in served.read() {
+        stats.customers_served += 1;
+        stats.total_wait += event.waited;
+        if event.satisfaction == Satisfaction::Unhappy {
+            stats.complaints += 1;
+        }
+    }
+    for _ in walked_out.read() {
+        stats.complaints += 1;
+    }
+}
+
+/// Ends the day once the shop is past closing and the
+/// last customer has left, or at midnight at the
+/// latest.
+fn close_up(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    closed: Option<Res<ClosedForTheDay>>,
+    customers: Query<Has<Leaving>, With<CustomerNpc>>,
+    mut next_state: ResMut<NextState<IsPaused>>,
+) {
+    if closed.is_some() || clock.is_open() {
+        return;
+    }
+    if !customers.is_empty() {
+        if clock.minutes < MINUTES_PER_DAY {
+            return;
+        }
+        // anyone still inside walks out, and is
+        // counted in `DayStats` the frame after
+        if customers.iter().any(|leaving| !leaving) {
+            commands.trigger(ClosingTime);
+            return;
+        }
+    }
+    info!(day = clock.day, "closed for the day");
+    commands.trigger(DayEnded { day: clock.day });
+    commands.insert_resource(ClosedForTheDay {
+        day: clock.day,
+    });
+    next_state.set(IsPaused::Paused);
+}
+
+fn start_next_day(
+    _trigger: Trigger<StartNextDay>,
+    mut commands: Commands,
+    mut clock: ResMut<GameClock>,
+    mut next_state: ResMut<NextState<IsPaused>>,
+) {
+    clock.start_next_day();
+    commands.insert_resource(DayStats::default());
+    commands.remove_resource::<ClosedForTheDay>();
+    next_state.set(IsPaused::Running);
+}
@@ -0,0 +1,311 @@
+use bevy::prelude::*;
+
+use crate::{
+    clock::GameClock,
+    customer_npc::{
+        OrderSettled, Satisfaction, SelfServiceCyclePaid,
+    },
+    states::AppState,
+};
+
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Wallet>()
+            .register_type::<Ledger>()
+            .init_resource::<Wallet>()
+            .init_resource::<Ledger>()
+            .observe(record_transaction)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                reset_books,
+            )
+            .add_systems(
+                Update,
+                (charge_orders, charge_self_service),
+            );
+    }
+}
+
+/// Money the shop starts out with, in cents
+const STARTING_BALANCE: i64 = 500_00;
+/// Share of the bill delighted customers leave on
+/// top
+const TIP_SHARE: f32 = 0.15;
+/// Share of the bill unhappy customers get back
+const REFUND_SHARE: f32 = 0.5;
+
+/// The shop's cash on hand, in cents. Only changes
+/// through a `Transaction`.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct Wallet {
+    balance: i64,
+}
+
+impl Default for Wallet {
+    fn default() -> Self {
+        Self {
+            balance: STARTING_BALANCE,
+        }
+    }
+}
+
+impl Wallet {
+    pub fn balance(&self) -> i64 {
+        self.balance
+    }
+
+    pub fn can_afford(&self, amount: u32) -> bool {
+        self.balance >= amount as i64
+    }
+}
+
+#[derive(
+    Debug, Reflect, Clone, Copy, PartialEq, Eq, Hash,
+)]
+pub enum TransactionKind {
+    CustomerPayment,
+    Tip,
+    Refund,
+    MachinePurchase,
+    MachineSale,
+    UtilityBill,
+    SupplyPurchase,
+}
+
+impl TransactionKind {
+    /// Whether money comes in or goes out
+    pub fn is_income(&self) -> bool {
+        matches!(
+            self,
+            TransactionKind::CustomerPayment
+                | TransactionKind::Tip
+                | TransactionKind::MachineSale
+        )
+    }
+}
+
+/// Money moving in or out of the `Wallet`. Trigger
+/// one to record it in the `Ledger`.
+#[derive(Event, Debug, Clone)]
+pub struct Transaction {
+    pub kind: TransactionKind,
+    /// in cents, always positive. The `kind` says
+    /// which way it goes.
+    pub amount: u32,
+    pub memo: String,
+}
+
+/// A `Transaction` as it was written down.
+#[derive(Debug, Reflect, Clone)]
+pub struct LedgerEntry {
+    pub day: u32,
+    /// Minutes since midnight
+    pub minutes: f32,
+    pub kind: TransactionKind,
+    /// in cents, negative for money going out
+    pub amount: i64,
+    pub memo: String,
+}
+
+/// Every `Transaction` since the shop opened, oldest
+/// first. Entries are never changed or removed.
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct Ledger {
+    entries: Vec<LedgerEntry>,
+}
+
+/// Money in and out over one day, in cents.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DayTotals {
+    pub income: i64,
+    pub expenses: i64,
+}
+
+impl DayTotals {
+    pub fn net(&self) -> i64 {
+        self.income - self.expenses
+    }
+}
+
+impl Ledger {
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    pub fn entries_on(
+        &self,
+        day: u32,
+    ) -> impl Iterator<Item = &LedgerEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.day == day)
+    }
+
+    pub fn totals_on(&self, day: u32) -> DayTotals {
+        self.entries_on(day).fold(
+            DayTotals::default(),
+            |mut totals, entry| {
+                if entry.amount >= 0 {
+                    totals.income += entry.amount;
+                } else {
+                    totals.expenses -= entry.amount;
+                }
+                totals
+            },
+        )
+    }
+
+    /// The net amount of one `kind` of transaction on
+    /// `day`, negative for expenses
+    pub fn total_on(
+        &self,
+        day: u32,
+        kind: TransactionKind,
+    ) -> i64 {
+        self.entries_on(day)
+            .filter(|entry| entry.kind == kind)
+            .map(|entry| entry.amount)
+            .sum()
+    }
+}
+
+fn reset_books(mut commands: Commands) {
+    commands.insert_resource(Wallet::default());
+    commands.insert_resource(Ledger::default());
+}
+
+fn record_transaction(
+    trigger: Trigger<Transaction>,
+    clock: Res<GameClock>,
+    mut wallet: ResMut<Wallet>,
+    mut ledger: ResMut<Ledger>,
+) {
+    let transaction = trigger.event();
+    let amount = if transaction.kind.is_income() {
+        transaction.amount as i64
+    } else {
+        -(transaction.amount as i64)
+    };
+    wallet.balance += amount;
+    info!(
+        kind = ?transaction.kind,
+        amount,
+        balance = wallet.balance,
+        memo = transaction.memo,
+        "transaction"
+    );
+    ledger.entries.push(LedgerEntry {
+        day: clock.day,
+        minutes: clock.minutes,
+        kind: transaction.kind,
+        amount,
+        memo: transaction.memo.clone(),
+    });
+}
+
+/// Customers pay for their order on pickup, tip when
+/// delighted and get some back when unhappy.
+fn charge_orders(
+    mut commands: Commands,
+    mut settled: EventReader<OrderSettled>,
+) {
+    for order in settled.read() {
+        let tier = order.tier.label();
+        commands.trigger(Transaction {
+            kind: TransactionKind::CustomerPayment,
+            amount: order.amount,
+            memo: format!("{tier} order"),
+        });
+        match order.satisfaction {
+            Satisfaction::Delighted => {
+                commands.trigger(Transaction {
+                    kind: TransactionKind::Tip,
+                    amount: share(order.amount, TIP_SHARE),
+                    memo: format!("tip on {tier} order"),
+                });
+            }
+            Satisfaction::Unhappy => {
+                commands.trigger(Transaction {
+                    kind: TransactionKind::Refund,
+                    amount: share(
+                        order.amount,
+                        REFUND_SHARE,
+                    ),
+                    memo: format!("refund on {tier} order"),
+                });
+            }
+            Satisfaction::Satisfied => {}
+        }
+    }
+}
+
+fn charge_self_service(
+    mut commands: Commands,
+    mut paid: EventReader<SelfServiceCyclePaid>,
+) {
+    for cycle in paid.read() {
+        commands.trigger(Transaction {
+            kind: TransactionKind::CustomerPayment,
+            amount: cycle.price,
+            memo: format!(
+                "self-service {}",
+                cycle.step.machine_name()
+            ),
+        });
+    }
+}
+
+fn share(amount: u32, share: f32) -> u32 {
+    (amount as f32 * share).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TransactionKind::*, *};
+
+    fn entry(
+        day: u32,
+        kind: TransactionKind,
+        amount: i64,
+    ) -> LedgerEntry {
+        LedgerEntry {
+            day,
+            minutes: 600.,
+            kind,
+            amount,
+            memo: String::new(),
+        }
+    }
+
+    #[test]
+    fn totals_on_splits_income_and_expenses() {
+        let ledger = Ledger {
+            entries: vec![
+                entry(0, CustomerPayment, 900),
+                entry(1, CustomerPayment, 500),
+                entry(1, Tip, 50),
+                entry(1, UtilityBill, -120),
+                entry(1, Refund, -30),
+                entry(2, MachinePurchase, -999),
+            ],
+        };
+        let totals = ledger.totals_on(1);
+        assert_eq!(totals.income, 550);
+        assert_eq!(totals.expenses, 150);
+        assert_eq!(totals.net(), 400);
+    }
+
+    #[test]
+    fn totals_on_a_quiet_day_are_zero() {
+        let ledger = Ledger {
+            entries: vec![entry(0, CustomerPayment, 900)],
+        };
+        let totals = ledger.totals_on(3);
+        assert_eq!(totals.income, 0);
+        assert_eq!(totals.expenses, 0);
+    }
+}
@@ -48,6 +48,7 @@ use crate::{
     inventory::Inventory,
     navmesh::{Obstacle, Spawner},
     states::{AppState, IsPaused},
+    steering::Crowd,
 };
 
 pub const PLAYER_COLLIDER_HEIGHT: f32 = 0.4;
@@ -222,6 +223,12 @@ impl Default for DropTimer {
 #[derive(Component)]
 pub struct Player;
 
+/// The name of the level blueprint that is loaded,
+/// without its extension. Used to look up per-level
+/// tuning data.
+#[derive(Resource, Debug, Clone)]
+pub struct CurrentLevel(pub String);
+
 fn setup_level(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -242,6 +249,10 @@ fn setup_level(
     //     Collider::half_space(Vec3::Y),
     // ));
     #[cfg(feature = "spawn_sacrifice")]
+    commands.insert_resource(CurrentLevel(
+        "Sacrifical Scene".into(),
+    ));
+    #[cfg(feature = "spawn_sacrifice")]
     commands.spawn((
         StateScoped(AppState::InGame),
         BlueprintInfo::from_path(
@@ -260,6 +271,8 @@ fn setup_level(
     //     GameWorldTag,
     // ));
     #[cfg(not(feature = "spawn_sacrifice"))]
+    commands.insert_resource(CurrentLevel("level-002".into()));
+    #[cfg(not(feature = "spawn_sacrifice"))]
     commands.spawn((
         StateScoped(AppState::InGame),
         BlueprintInfo::from_path("levels/level-002.glb"),
@@ -401,10 +414,9 @@ fn spawn_player(
                 GameLayer::Player,
                 [GameLayer::Enemy, GameLayer::Ground],
             ),
-            Inventory {
-                max_item_count: 20,
-                items: vec![],
-            },
+            Inventory { max_volume: 30 },
+            // customers walk around the player
+            Crowd { radius: 0.3 },
         ))
         .with_children(|builder| {
             let half_height = 0.05;
@@ -21,6 +21,7 @@ use crate::{
     customer_npc::CustomerNpcSpawnEvent,
     navmesh::{Object, SpawnObstacle},
     states::{AppState, GameMode},
+    utilities::OrderDetergent,
     widgets::{self, *},
 };
 
@@ -91,6 +92,38 @@ pub fn spawn_game_menu(
             },
         ),
     ));
+    buttons.add::<MainMenuButtonWidget>((
+        MainMenuButtonWidgetBundle {
+            props: MainMenuButtonWidget {
+                content: "Contracts".to_string(),
+                offset: 150,
+                ..default()
+            },
+            ..default()
+        },
+        On::<Pointer<Click>>::run(
+            |mut boards: Query<&mut ContractBoard>| {
+                for mut board in &mut boards {
+                    board.show_modal = !board.show_modal;
+                }
+            },
+        ),
+    ));
+    buttons.add::<MainMenuButtonWidget>((
+        MainMenuButtonWidgetBundle {
+            props: MainMenuButtonWidget {
+                content: "Order Detergent".to_string(),
+                offset: 200,
+                ..default()
+            },
+            ..default()
+        },
+        On::<Pointer<Click>>::run(
+            |mut commands: Commands| {
+                commands.trigger(OrderDetergent);
+            },
+        ),
+    ));
     let root = commands
         .spawn((
             StateScoped(AppState::InGame),
@@ -142,6 +175,64 @@ pub fn spawn_game_menu(
                             },
                             ..default()
                         },
+                    )
+                    .with_child::<ContractBoard>(
+                        ContractBoardBundle {
+                            styles: WoodpeckerStyle {
+                                width: Units::Percentage(100.0),
+                                justify_content: Some(
+                                    WidgetAlignContent::Center,
+                                ),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    )
+                    .with_child::<MachineUpgradeMenu>(
+                        MachineUpgradeMenuBundle {
+                            styles: WoodpeckerStyle {
+                                width: Units::Percentage(100.0),
+                                justify_content: Some(
+                                    WidgetAlignContent::Center,
+                                ),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    )
+                    .with_child::<DaySummary>(
+                        DaySummaryBundle {
+                            styles: WoodpeckerStyle {
+                                width: Units::Percentage(100.0),
+                                justify_content: Some(
+                                    WidgetAlignContent::Center,
+                                ),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    )
+                    .with_child::<OrderBoard>(
+                        OrderBoardBundle {
+                            styles: WoodpeckerStyle {
+                                position: WidgetPosition::Absolute,
+                                right: Units::Pixels(10.),
+                                top: Units::Pixels(10.),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    )
+                    .with_child::<MachineShopPanel>(
+                        MachineShopPanelBundle {
+                            styles: WoodpeckerStyle {
+                                position: WidgetPosition::Absolute,
+                                left: Units::Pixels(10.),
+                                bottom: Units::Pixels(10.),
+                                ..default()
+                            },
+                            ..default()
+                        },
                     ),
                     ..default()
                 }),
@@ -9,24 +9,44 @@ use bevy::{
         },
         world::DeferredWorld,
     },
-    math::{ivec3, vec3},
+    math::vec3,
     prelude::*,
     utils::HashMap,
 };
 use bevy_mod_picking::{
     events::{Click, Pointer},
-    prelude::On,
+    prelude::{Listener, On, PointerButton},
 };
 use bevy_mod_raycast::prelude::*;
 
 use crate::{
+    assets::GameDataAssets,
     camera::GameCamera,
-    inventory::{Inventory, ProcessedState},
+    economy::{Transaction, TransactionKind, Wallet},
     game_scene::{
         InvalidRangeToObject, Player,
         PlayerMachineRangeSensor, WashingMachine,
     },
+    inventory::{
+        held_weight, HeldBy, Inventory, InventoryTransfer,
+        Item, ItemFilter, ItemsTransferred, ProcessedState,
+        Ruined, Stained,
+    },
+    machine_shop::{
+        MachineCatalog, MachineListing, MachineTier,
+        PurchasePrice,
+    },
+    processing::{
+        condition_change, MachineProgram, ProcessingStep,
+        STAIN_CONDITION_LOSS,
+    },
+    rng::{GameRng, RngStream},
     states::{GameMode, IsPaused},
+    utilities::{
+        use_supplies, CycleUsage, DetergentStock,
+        UtilityMeter,
+    },
+    widgets::MachineUpgradeMenu,
 };
 
 pub struct GridPlugin;
@@ -34,19 +54,23 @@ pub struct GridPlugin;
 impl Plugin for GridPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<BlenderOnClick>()
+            .register_type::<MachineKind>()
+            .register_type::<MaxLoad>()
             .init_resource::<GridStore>()
+            .init_resource::<SelectedMachineKind>()
             .add_plugins(DeferredRaycastingPlugin::<
                 VirtualGridRaycast,
             >::default())
             .add_systems(
                 Update,
-                raycast_system.run_if(in_state(
-                    GameMode::VirtualGridPlacement,
-                )),
+                (select_machine_kind, raycast_system).run_if(
+                    in_state(GameMode::VirtualGridPlacement),
+                ),
             )
             .add_systems(
                 Update,
-                do_work.run_if(in_state(IsPaused::Running)),
+                (do_work, clear_emptied_machines)
+                    .run_if(in_state(IsPaused::Running)),
             )
             .add_systems(
                 OnEnter(GameMode::VirtualGridPlacement),
@@ -57,16 +81,68 @@ impl Plugin for GridPlugin {
                 exit_virtual_grid_placement,
             )
             .observe(test)
-            .observe(interact_with_machine);
+            .observe(interact_with_machine)
+            .observe(cycle_machine_program)
+            .observe(upgrade_machine)
+            .observe(add_machine_lamp);
     }
 }
 
+/// The machine standing in each occupied grid cell
 #[derive(Resource, Debug, Default, Deref, DerefMut)]
-struct GridStore(HashMap<IVec3, bool>);
+struct GridStore(HashMap<IVec3, Entity>);
 
 #[derive(TypePath)]
 struct VirtualGridRaycast;
 
+/// The `ProcessingStep` a machine performs on the
+/// items loaded into it.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct MachineKind(pub ProcessingStep);
+
+/// The most weight, in kilograms, a machine can
+/// process in one go.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct MaxLoad(pub f32);
+
+/// How much room a machine has for every kilogram it
+/// can take. Light items fill the room before the
+/// weight limit, dense ones can go over it.
+const VOLUME_PER_KG: f32 = 4.;
+
+impl MaxLoad {
+    /// The room for items a machine with this load
+    /// limit has
+    pub fn volume(&self) -> u32 {
+        (self.0 * VOLUME_PER_KG).ceil() as u32
+    }
+}
+
+/// A machine that refused to start because there was
+/// too much in it. It has to be emptied before it
+/// can be used again.
+#[derive(Component, Debug)]
+pub struct Overloaded;
+
+/// A self-service customer has their laundry in this
+/// machine. The player can't use it until they've
+/// taken it back out.
+#[derive(Component, Debug)]
+pub struct InUseBy(pub Entity);
+
+/// Which kind of machine a click in
+/// `GameMode::VirtualGridPlacement` places.
+#[derive(Resource, Debug)]
+pub struct SelectedMachineKind(pub ProcessingStep);
+
+impl Default for SelectedMachineKind {
+    fn default() -> Self {
+        Self(ProcessingStep::Wash)
+    }
+}
+
 fn exit_virtual_grid_placement(
     mut commands: Commands,
     current_cameras: Query<Entity, With<GameCamera>>,
@@ -110,16 +186,27 @@ fn spawn_virtual_placement_grid(
     ));
 }
 
+/// A machine partway through a cycle
 #[derive(Component)]
-struct Working(Timer);
+pub struct Working(Timer);
 
 #[derive(Component)]
 struct DefaultWorkDuration(Duration);
 
+/// Extra condition a machine gives items for every
+/// step they needed. Upgrades raise it.
+#[derive(Component, Debug)]
+struct QualityBonus(f32);
+
 fn start_work(
     trigger: Trigger<StartWork>,
     mut commands: Commands,
     default_work_durations: Query<&DefaultWorkDuration>,
+    max_loads: Query<&MaxLoad>,
+    usages: Query<(&CycleUsage, &MachineProgram)>,
+    items: Query<(&Item, &HeldBy)>,
+    mut detergent: ResMut<DetergentStock>,
+    mut meter: ResMut<UtilityMeter>,
 ) {
     let Ok(duration) =
         default_work_durations.get(trigger.entity())
@@ -127,45 +214,223 @@ fn start_work(
         warn!("DefaultWorkDuration component should exist on Machine");
         return;
     };
+    // `StartWork` follows a transfer that may not have
+    // moved anything. Empty machines don't run, and
+    // don't use any supplies.
+    let loaded = items
+        .iter()
+        .any(|(_, held_by)| held_by.0 == trigger.entity());
+    if !loaded {
+        info!(
+            machine = ?trigger.entity(),
+            "nothing to wash"
+        );
+        return;
+    }
+    if let Ok(max_load) = max_loads.get(trigger.entity()) {
+        let load = held_weight(&items, trigger.entity());
+        if load > max_load.0 {
+            warn!(
+                load,
+                max_load = max_load.0,
+                "machine overloaded"
+            );
+            commands
+                .entity(trigger.entity())
+                .insert(Overloaded);
+            return;
+        }
+    }
+    if let Ok((usage, program)) =
+        usages.get(trigger.entity())
+    {
+        let usage = usage.for_program(*program);
+        if !use_supplies(usage, &mut detergent, &mut meter)
+        {
+            warn!(
+                needed = usage.detergent,
+                stock = detergent.doses,
+                "out of detergent"
+            );
+            return;
+        }
+    }
     commands.entity(trigger.entity()).insert(Working(
         Timer::new(duration.0, TimerMode::Once),
     ));
 }
 
+/// A machine that finished its cycle and is waiting
+/// to be emptied
 #[derive(Component, Debug)]
-struct Done;
+pub struct Done;
+
+/// A finished or overloaded machine is ready to be
+/// used again once everything has been taken out.
+fn clear_emptied_machines(
+    mut commands: Commands,
+    mut transferred: EventReader<ItemsTransferred>,
+    machines: Query<(), Or<(With<Done>, With<Overloaded>)>>,
+    items: Query<&HeldBy, With<Item>>,
+) {
+    for event in transferred.read() {
+        if !machines.contains(event.from) {
+            continue;
+        }
+        let emptied = !items
+            .iter()
+            .any(|held_by| held_by.0 == event.from);
+        if emptied {
+            commands
+                .entity(event.from)
+                .remove::<(Done, Overloaded)>();
+        }
+    }
+}
 
 fn do_work(
-    mut query: Query<(
-        Entity,
-        &mut Working,
-        &mut Inventory,
-    )>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Working,
+            &MachineKind,
+            &MachineProgram,
+            &QualityBonus,
+        ),
+        With<Inventory>,
+    >,
+    mut items: Query<(Entity, &mut Item, &HeldBy)>,
     time: Res<Time>,
+    mut game_rng: ResMut<GameRng>,
     mut commands: Commands,
 ) {
-    for (entity, mut working, mut inventory) in &mut query {
+    let rng = game_rng.stream(RngStream::Processing);
+    for (
+        entity,
+        mut working,
+        machine_kind,
+        program,
+        quality_bonus,
+    ) in &mut query
+    {
         if working.0.tick(time.delta()).just_finished() {
             info!(?entity, "done");
-            // TODO: Inventory Item state must change after
-            // being worked on
             commands
                 .entity(entity)
                 .remove::<Working>()
                 .insert(Done);
-            for mut item in inventory.items.iter_mut() {
-                item.process();
+            // a single pen left in a pocket is enough to
+            // ruin the whole load
+            let contaminated = machine_kind.0
+                == ProcessingStep::Wash
+                && items.iter().any(|(_, item, held_by)| {
+                    held_by.0 == entity && !item.washable
+                });
+            // one red sock is all it takes
+            let load_bleeds = machine_kind.0
+                == ProcessingStep::Wash
+                && items.iter().any(|(_, item, held_by)| {
+                    held_by.0 == entity
+                        && item.washable
+                        && item.color.bleeds()
+                });
+
+            for (item_entity, mut item, _) in
+                items.iter_mut().filter(|(_, _, held_by)| {
+                    held_by.0 == entity
+                })
+            {
+                if item.washable {
+                    // items that don't need this step
+                    // still get tumbled around with the
+                    // rest of the load
+                    let needed = item.next_step()
+                        == Some(machine_kind.0);
+                    let mut change = condition_change(
+                        &item,
+                        machine_kind.0,
+                        needed,
+                        *program,
+                        load_bleeds,
+                        rng,
+                    );
+                    if needed {
+                        change += quality_bonus.0;
+                    }
+                    if change < 0. {
+                        info!(
+                            name = item.name,
+                            change,
+                            "item damaged"
+                        );
+                    }
+                    item.change_condition(change);
+                }
+                item.process(machine_kind.0);
+
+                if !contaminated {
+                    continue;
+                }
+                if item.washable {
+                    item.change_condition(
+                        -STAIN_CONDITION_LOSS,
+                    );
+                    commands
+                        .entity(item_entity)
+                        .insert(Stained);
+                } else {
+                    commands
+                        .entity(item_entity)
+                        .insert(Ruined);
+                }
+            }
+            if contaminated {
+                info!(?entity, "load contaminated");
             }
         }
     }
 }
 
+fn select_machine_kind(
+    input: Res<ButtonInput<KeyCode>>,
+    mut selected: ResMut<SelectedMachineKind>,
+) {
+    let keys = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+    ];
+    for (key, step) in keys.iter().zip(ProcessingStep::ALL)
+    {
+        if input.just_pressed(*key) {
+            info!(machine = step.machine_name(), "selected");
+            selected.0 = step;
+        }
+    }
+}
+
+/// Left click buys the selected machine and places
+/// it, right click sells the machine under the cursor.
 fn raycast_system(
     mut commands: Commands,
     mut gizmos: Gizmos,
     query: Query<&RaycastMesh<VirtualGridRaycast>>,
     input: Res<ButtonInput<MouseButton>>,
     mut grid_store: ResMut<GridStore>,
+    selected: Res<SelectedMachineKind>,
+    wallet: Res<Wallet>,
+    game_data: Res<GameDataAssets>,
+    machine_catalogs: Res<Assets<MachineCatalog>>,
+    machines: Query<
+        (
+            &MachineKind,
+            &PurchasePrice,
+            Has<Working>,
+            Has<InUseBy>,
+        ),
+    >,
+    items: Query<&HeldBy, With<Item>>,
 ) {
     for raycast_mesh in &query {
         for (entity, intersection_data) in
@@ -176,50 +441,188 @@ fn raycast_system(
                 Transform::from_translation(pos),
                 PINK_400,
             );
-            if input.just_pressed(MouseButton::Left) {
-                if grid_store.get(&pos.as_ivec3()).is_none()
-                {
-                    commands.spawn((
-                    crate::navmesh::Obstacle,
-                    blenvy::BlueprintInfo::from_path(
-                        "blueprints/washing_machine.glb",
-                    ),
-                    blenvy::SpawnBlueprint,
-                    TransformBundle::from_transform(
-                        Transform::from_translation(pos),
-                    ),
-                    Inventory{
-                        max_item_count: 5,
-                        items: vec![],
-                    },
-                    DefaultWorkDuration(Duration::from_secs(10))
-                )).observe(start_work);
-
-                    grid_store.insert(pos.as_ivec3(), true);
-                } else if grid_store
-                    .get(&(pos.as_ivec3() + ivec3(0, 1, 0)))
-                    .is_none()
-                {
-                    let pos =
-                        pos.as_ivec3() + ivec3(0, 1, 0);
-                    commands.spawn((
-                            crate::navmesh::Obstacle,
-                            blenvy::BlueprintInfo::from_path(
-                                "blueprints/washing_machine.glb",
-                            ),
-                            blenvy::SpawnBlueprint,
-                            TransformBundle::from_transform(
-                                Transform::from_translation(pos.as_vec3()),
-                            ),
-                        ));
-                } else {
-                    info!("blocked");
-                }
+            let cell = pos.as_ivec3();
+
+            if input.just_pressed(MouseButton::Right) {
+                sell_machine(
+                    &mut commands,
+                    &mut grid_store,
+                    cell,
+                    &machines,
+                    &items,
+                );
+                continue;
+            }
+            if !input.just_pressed(MouseButton::Left) {
+                continue;
             }
+
+            if grid_store.contains_key(&cell) {
+                info!("blocked");
+                continue;
+            }
+            let Some(listing) = machine_catalogs
+                .get(&game_data.machine_catalog)
+                .and_then(|catalog| {
+                    catalog.listing(selected.0)
+                })
+            else {
+                warn!(
+                    machine = selected.0.machine_name(),
+                    "machine is not for sale"
+                );
+                continue;
+            };
+            if !wallet.can_afford(listing.price) {
+                info!(
+                    price = listing.price,
+                    balance = wallet.balance(),
+                    "can't afford machine"
+                );
+                continue;
+            }
+            commands.trigger(Transaction {
+                kind: TransactionKind::MachinePurchase,
+                amount: listing.price,
+                memo: selected.0.machine_name().to_string(),
+            });
+            let machine = spawn_machine(
+                &mut commands,
+                cell.as_vec3(),
+                listing,
+            );
+            grid_store.insert(cell, machine);
         }
     }
 }
 
+fn spawn_machine(
+    commands: &mut Commands,
+    position: Vec3,
+    listing: &MachineListing,
+) -> Entity {
+    let step = listing.step;
+    commands
+        .spawn((
+            Name::new(step.machine_name()),
+            crate::navmesh::Obstacle,
+            blenvy::BlueprintInfo::from_path(
+                &listing.blueprint,
+            ),
+            blenvy::SpawnBlueprint,
+            TransformBundle::from_transform(
+                Transform::from_translation(position),
+            ),
+            Inventory {
+                max_volume: MaxLoad(step.max_load())
+                    .volume(),
+            },
+            MaxLoad(step.max_load()),
+            DefaultWorkDuration(Duration::from_secs(10)),
+            MachineKind(step),
+            MachineProgram::default(),
+            PurchasePrice(listing.price),
+            listing.usage,
+            MachineTier::default(),
+            QualityBonus(0.),
+        ))
+        .observe(start_work)
+        .id()
+}
+
+/// How far apart the lamps on top of a machine are
+const LAMP_SPACING: f32 = 0.3;
+
+/// Puts lamps in the color of the machine's
+/// `ProcessingStep` on top of it, one more for every
+/// upgrade. Every machine uses the same model, and an
+/// upgrade swapping the blueprint takes the old lamps
+/// with it.
+fn add_machine_lamp(
+    trigger: Trigger<OnAdd, blenvy::SpawnBlueprint>,
+    mut commands: Commands,
+    machines: Query<(&MachineKind, &MachineTier)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Ok((kind, tier)) = machines.get(trigger.entity())
+    else {
+        return;
+    };
+    let color = kind.0.machine_color();
+    let mesh = meshes.add(Sphere::new(0.12));
+    let material = materials.add(StandardMaterial {
+        base_color: color.into(),
+        emissive: LinearRgba::from(color) * 4.,
+        ..default()
+    });
+    let count = tier.0 + 1;
+    // centered over the machine
+    let first = -LAMP_SPACING * (count - 1) as f32 / 2.;
+    commands.entity(trigger.entity()).with_children(
+        |builder| {
+            for lamp in 0..count {
+                builder.spawn((
+                    Name::new("MachineLamp"),
+                    PbrBundle {
+                        mesh: mesh.clone(),
+                        material: material.clone(),
+                        transform: Transform::from_xyz(
+                            first
+                                + LAMP_SPACING
+                                    * lamp as f32,
+                            1.2,
+                            0.,
+                        ),
+                        ..default()
+                    },
+                ));
+            }
+        },
+    );
+}
+
+/// Machines can only be sold while they are empty and
+/// idle.
+fn sell_machine(
+    commands: &mut Commands,
+    grid_store: &mut GridStore,
+    cell: IVec3,
+    machines: &Query<
+        (
+            &MachineKind,
+            &PurchasePrice,
+            Has<Working>,
+            Has<InUseBy>,
+        ),
+    >,
+    items: &Query<&HeldBy, With<Item>>,
+) {
+    let Some(&machine) = grid_store.get(&cell) else {
+        return;
+    };
+    let Ok((kind, price, working, in_use)) =
+        machines.get(machine)
+    else {
+        warn!(?machine, "machine has no purchase price");
+        return;
+    };
+    let loaded =
+        items.iter().any(|held_by| held_by.0 == machine);
+    if working || in_use || loaded {
+        info!(?machine, "empty the machine before selling");
+        return;
+    }
+
+    commands.trigger(Transaction {
+        kind: TransactionKind::MachineSale,
+        amount: price.resale_value(),
+        memo: kind.0.machine_name().to_string(),
+    });
+    commands.entity(machine).despawn_recursive();
+    grid_store.remove(&cell);
+}
+
 fn test(
     trigger: Trigger<BlenderOnClick>,
     mut local: Local<u8>,
@@ -236,20 +639,154 @@ struct MachineInteract {
 #[derive(Event)]
 pub struct StartWork;
 
+#[derive(Event)]
+struct CycleMachineProgram {
+    machine_entity: Entity,
+}
+
+/// Buy the next upgrade for a machine.
+#[derive(Event)]
+pub struct UpgradeMachine {
+    pub machine_entity: Entity,
+}
+
+/// Upgrades can only be installed while a machine is
+/// idle.
+fn upgrade_machine(
+    trigger: Trigger<UpgradeMachine>,
+    mut commands: Commands,
+    wallet: Res<Wallet>,
+    game_data: Res<GameDataAssets>,
+    machine_catalogs: Res<Assets<MachineCatalog>>,
+    mut machines: Query<
+        (
+            &MachineKind,
+            &mut MachineTier,
+            &mut PurchasePrice,
+            &mut Inventory,
+            &mut MaxLoad,
+            &mut DefaultWorkDuration,
+            &mut QualityBonus,
+            Has<InUseBy>,
+        ),
+        (Without<Working>, Without<Done>),
+    >,
+    items: Query<&HeldBy, With<Item>>,
+) {
+    let machine = trigger.event().machine_entity;
+    let Ok((
+        kind,
+        mut tier,
+        mut price,
+        mut inventory,
+        mut max_load,
+        mut work_duration,
+        mut quality_bonus,
+        in_use,
+    )) = machines.get_mut(machine)
+    else {
+        info!("machine is busy");
+        return;
+    };
+    let loaded =
+        items.iter().any(|held_by| held_by.0 == machine);
+    if in_use || loaded {
+        info!(
+            ?machine,
+            "empty the machine before upgrading"
+        );
+        return;
+    }
+    let Some(upgrade) = machine_catalogs
+        .get(&game_data.machine_catalog)
+        .and_then(|catalog| catalog.listing(kind.0))
+        .and_then(|listing| listing.next_upgrade(*tier))
+    else {
+        info!(?machine, "no upgrades left");
+        return;
+    };
+    if !wallet.can_afford(upgrade.price) {
+        info!(
+            price = upgrade.price,
+            balance = wallet.balance(),
+            "can't afford upgrade"
+        );
+        return;
+    }
+
+    commands.trigger(Transaction {
+        kind: TransactionKind::MachinePurchase,
+        amount: upgrade.price,
+        memo: format!(
+            "{} upgrade: {}",
+            kind.0.machine_name(),
+            upgrade.name
+        ),
+    });
+    tier.0 += 1;
+    price.0 += upgrade.price;
+    max_load.0 = upgrade.max_load;
+    inventory.max_volume = max_load.volume();
+    work_duration.0 =
+        Duration::from_secs_f32(upgrade.cycle_seconds);
+    quality_bonus.0 = upgrade.quality_bonus;
+    info!(?machine, upgrade = upgrade.name, "upgraded");
+
+    // swap the model for the upgraded variant
+    commands.entity(machine).despawn_descendants().insert(
+        (
+            blenvy::BlueprintInfo::from_path(
+                &upgrade.blueprint,
+            ),
+            blenvy::SpawnBlueprint,
+        ),
+    );
+}
+
+/// Programs can only be changed while a machine is
+/// idle.
+fn cycle_machine_program(
+    trigger: Trigger<CycleMachineProgram>,
+    mut programs: Query<
+        &mut MachineProgram,
+        Without<Working>,
+    >,
+) {
+    let Ok(mut program) =
+        programs.get_mut(trigger.event().machine_entity)
+    else {
+        info!("machine is busy");
+        return;
+    };
+    *program = program.next();
+    info!(?program, "machine program");
+}
+
 fn interact_with_machine(
     trigger: Trigger<MachineInteract>,
-    mut machines: Query<
-        (Entity, &mut Inventory, Option<&Done>),
-        (With<WashingMachine>, Without<Working>),
+    machines: Query<
+        (
+            Entity,
+            Option<&Done>,
+            Has<Overloaded>,
+            Has<InUseBy>,
+        ),
+        (
+            With<WashingMachine>,
+            With<Inventory>,
+            Without<Working>,
+        ),
     >,
-    mut player: Query<
-        (Entity, &mut Inventory),
+    player: Query<
+        Entity,
         (With<Player>, Without<WashingMachine>),
     >,
     player_machine_sensor: Query<
         &CollidingEntities,
         With<PlayerMachineRangeSensor>,
     >,
+    usages: Query<(&CycleUsage, &MachineProgram)>,
+    detergent: Res<DetergentStock>,
     mut commands: Commands,
 ) {
     info!(
@@ -268,59 +805,71 @@ fn interact_with_machine(
         return;
     };
 
-    let Ok((player_entity, mut player_inventory)) =
-        player.get_single_mut()
-    else {
+    let Ok(player_entity) = player.get_single() else {
         warn!("expected exactly 1 player");
         return;
     };
 
     dbg!(machines.get(trigger.event().machine_entity));
-    let Ok((machine_entity, mut machine_inventory, done)) =
-        machines.get_mut(trigger.event().machine_entity)
+    let Ok((machine_entity, done, overloaded, in_use)) =
+        machines.get(trigger.event().machine_entity)
     else {
         warn!("expected exactly 1 machine");
         return;
     };
 
+    // hands off other people's laundry
+    if in_use {
+        info!(?machine_entity, "machine is in use");
+        return;
+    }
+
     // machines_done_with_work
 
     if player_sensor.contains(&machine_entity)
         && done.is_none()
+        && !overloaded
     {
-        // drop off into machine
-        let available_space = machine_inventory
-            .max_item_count
-            - machine_inventory.items.len();
-
-        let item_range = 0..(player_inventory
-            .items
-            .len()
-            .min(available_space));
-
-        let transition_items =
-            player_inventory.items.drain(item_range);
-
-        machine_inventory.items.extend(transition_items);
+        // check before loading, a machine that can't
+        // start would keep the laundry locked inside
+        let out_of_detergent = usages
+            .get(machine_entity)
+            .is_ok_and(|(usage, program)| {
+                let usage = usage.for_program(*program);
+                !detergent.covers(usage)
+            });
+        if out_of_detergent {
+            warn!(?machine_entity, "out of detergent");
+            return;
+        }
+        // drop off into machine. Loose items that
+        // can't be washed stay with the player, but
+        // anything still in a pocket goes in too.
+        commands.trigger(InventoryTransfer {
+            from: player_entity,
+            to: machine_entity,
+            filter: ItemFilter {
+                washable: Some(true),
+                ..default()
+            },
+            all_or_nothing: false,
+            max_weight: None,
+        });
         commands.trigger_targets(StartWork, machine_entity);
     } else if player_sensor.contains(&machine_entity)
-        && done.is_some()
+        && (done.is_some() || overloaded)
     {
-        // pickup from machine
-        let available_space = player_inventory
-            .max_item_count
-            - player_inventory.items.len();
-
-        let item_range = 0..(machine_inventory
-            .items
-            .len()
-            .min(available_space));
-
-        let transition_items =
-            machine_inventory.items.drain(item_range);
-
-        player_inventory.items.extend(transition_items);
-        commands.entity(machine_entity).remove::<Done>();
+        // pickup from machine, or take everything back
+        // out of one that was too full to start. The
+        // load comes out in one go, see
+        // `clear_emptied_machines`.
+        commands.trigger(InventoryTransfer {
+            from: machine_entity,
+            to: player_entity,
+            filter: ItemFilter::default(),
+            all_or_nothing: true,
+            max_weight: None,
+        });
     } else {
         // fire invalid machine choice by range
         commands.trigger_targets(
@@ -361,16 +910,34 @@ impl Component for BlenderOnClick {
                         "machine_interact" => {
                             world.commands().entity(entity).insert(
                                 On::<Pointer<Click>>::run(
-                                    move |mut commands: Commands| {
+                                    move |event: Listener<Pointer<Click>>,
+                                          mut commands: Commands,
+                                          mut menus: Query<&mut MachineUpgradeMenu>| {
                                         info!(
                                             ?observer_name,
                                             "on click machine"
                                         );
-                                        commands.trigger(
-                                            MachineInteract {
-                                                machine_entity: entity
+                                        match event.button {
+                                            PointerButton::Middle => {
+                                                for mut menu in &mut menus {
+                                                    menu.machine = Some(entity);
+                                                }
                                             }
-                                        );
+                                            PointerButton::Secondary => {
+                                                commands.trigger(
+                                                    CycleMachineProgram {
+                                                        machine_entity: entity
+                                                    }
+                                                );
+                                            }
+                                            _ => {
+                                                commands.trigger(
+                                                    MachineInteract {
+                                                        machine_entity: entity
+                                                    }
+                                                );
+                                            }
+                                        }
                                     },
                                 ),
                             );
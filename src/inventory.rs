@@ -1,32 +1,71 @@
 use bevy::prelude::*;
 
-use crate::persistent_id::PersistentId;
+use crate::{
+    item_catalog::{Fabric, ItemDefinition, LaundryColor},
+    persistent_id::PersistentId,
+    processing::ProcessingStep,
+};
 
 pub struct InventoryPlugin;
 
 impl Plugin for InventoryPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Inventory>()
-            .register_type::<Item>();
+            .register_type::<Item>()
+            .register_type::<HeldBy>()
+            .register_type::<InPocket>()
+            .register_type::<Stained>()
+            .register_type::<Ruined>()
+            .add_event::<ItemsTransferred>()
+            .add_event::<PocketsEmptied>()
+            .add_systems(PostUpdate, despawn_orphaned_items)
+            .observe(transfer_items)
+            .observe(check_pockets);
     }
 }
 
-#[derive(Debug, Reflect, PartialEq)]
+/// The furthest an item has made it through the
+/// `LaundryPipeline`.
+#[derive(
+    Debug,
+    Reflect,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
 pub enum ProcessedState {
-    Unprocessed,
-    Processed,
+    Dirty,
+    Washed,
+    Dried,
+    Pressed,
+    Folded,
 }
 
+/// How much volume worth of `Item`s an entity can
+/// hold. A sock takes up less room than a duvet.
+///
+/// The items themselves are separate entities that
+/// point back at their holder using `HeldBy`.
 #[derive(Debug, Component, Reflect)]
 #[reflect(Component)]
 pub struct Inventory {
-    pub max_item_count: usize,
-    pub items: Vec<Item>,
+    pub max_volume: u32,
 }
 
 impl Inventory {
-    pub fn has_available_space(&self) -> bool {
-        self.items.len() < self.max_item_count
+    pub fn has_available_space(
+        &self,
+        held_volume: u32,
+        volume: u32,
+    ) -> bool {
+        held_volume + volume <= self.max_volume
+    }
+
+    pub fn available_space(&self, held_volume: u32) -> u32 {
+        self.max_volume.saturating_sub(held_volume)
     }
 }
 
@@ -36,11 +75,654 @@ pub struct Item {
     pub name: String,
     pub owner: Option<PersistentId>,
     pub state: ProcessedState,
+    /// The steps the owner asked for, in pipeline
+    /// order
+    pub order: Vec<ProcessingStep>,
+    /// Non-washable items ruin the rest of a wash
+    /// cycle they end up in
+    pub washable: bool,
+    /// How much room the item takes up in an
+    /// `Inventory`
+    pub volume: u32,
+    /// in kilograms
+    pub weight: f32,
+    /// in cents, what the `ItemDefinition` charges
+    /// for it
+    pub base_price: u32,
+    pub fabric: Fabric,
+    pub color: LaundryColor,
+    /// How good the item looks, from 0.0 (unwearable)
+    /// to 1.0 (like new). Every processing step can
+    /// raise or lower it.
+    pub condition: f32,
 }
 
 impl Item {
-    pub fn process(&mut self) -> &mut Self {
-        self.state = ProcessedState::Processed;
-        self
+    pub fn new(
+        definition: &ItemDefinition,
+        owner: Option<PersistentId>,
+        order: Vec<ProcessingStep>,
+        color: LaundryColor,
+    ) -> Self {
+        Self {
+            name: definition.name.clone(),
+            owner,
+            state: ProcessedState::Dirty,
+            order,
+            washable: definition.washable,
+            volume: definition.volume,
+            weight: definition.weight,
+            base_price: definition.base_price,
+            fabric: definition.fabric,
+            color,
+            condition: Self::DROPOFF_CONDITION,
+        }
+    }
+
+    /// Laundry comes in worn, which leaves room for
+    /// the shop to make it look better than it did.
+    pub const DROPOFF_CONDITION: f32 = 0.7;
+
+    pub fn change_condition(&mut self, amount: f32) {
+        self.condition =
+            (self.condition + amount).clamp(0., 1.);
+    }
+
+    /// The first ordered step this item has not been
+    /// through yet
+    pub fn next_step(&self) -> Option<ProcessingStep> {
+        self.order
+            .iter()
+            .copied()
+            .find(|step| step.result() > self.state)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_step().is_none()
+    }
+
+    /// Advances the item if `step` is the next step in
+    /// its order. Returns whether anything changed.
+    pub fn process(
+        &mut self,
+        step: ProcessingStep,
+    ) -> bool {
+        if self.next_step() != Some(step) {
+            return false;
+        }
+        self.state = step.result();
+        true
+    }
+}
+
+/// The player, machine, or customer whose
+/// `Inventory` an `Item` is currently in.
+#[derive(
+    Debug, Component, Reflect, Clone, Copy, PartialEq, Eq,
+)]
+#[reflect(Component)]
+pub struct HeldBy(pub Entity);
+
+/// An item tucked into the pocket of another item.
+///
+/// Pocket contents travel with the item whose pocket
+/// they're in until someone checks the pockets.
+#[derive(Debug, Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct InPocket(pub Entity);
+
+/// Washed together with something that wasn't
+/// washable, like a pen.
+#[derive(Debug, Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Stained;
+
+/// A non-washable item that went through a wash.
+#[derive(Debug, Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Ruined;
+
+/// The total volume of the items held by `holder`.
+/// Whatever is in a pocket takes up no room of its
+/// own.
+pub fn held_volume<'a>(
+    items: impl IntoIterator<
+        Item = (&'a Item, &'a HeldBy, Option<&'a InPocket>),
+    >,
+    holder: Entity,
+) -> u32 {
+    items
+        .into_iter()
+        .filter(|(_, held_by, in_pocket)| {
+            held_by.0 == holder && in_pocket.is_none()
+        })
+        .map(|(item, _, _)| item.volume)
+        .sum()
+}
+
+/// The total weight of the items held by `holder`
+pub fn held_weight<'a>(
+    items: impl IntoIterator<Item = (&'a Item, &'a HeldBy)>,
+    holder: Entity,
+) -> f32 {
+    items
+        .into_iter()
+        .filter(|(_, held_by)| held_by.0 == holder)
+        .map(|(item, _)| item.weight)
+        .sum()
+}
+
+/// Items leave the world with their holder, for
+/// example when a customer walks out the door with
+/// their clean laundry.
+fn despawn_orphaned_items(
+    mut commands: Commands,
+    items: Query<(Entity, &HeldBy), With<Item>>,
+    holders: Query<(), With<Inventory>>,
+) {
+    for (entity, held_by) in &items {
+        if !holders.contains(held_by.0) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Selects which items an `InventoryTransfer` moves.
+///
+/// A field left as `None` matches every item.
+#[derive(Debug, Default, Clone)]
+pub struct ItemFilter {
+    pub owner: Option<PersistentId>,
+    pub state: Option<ProcessedState>,
+    pub name: Option<String>,
+    pub finished: Option<bool>,
+    pub washable: Option<bool>,
+    /// Only items that go through this step next
+    pub next_step: Option<ProcessingStep>,
+}
+
+impl ItemFilter {
+    pub fn matches(&self, item: &Item) -> bool {
+        let owner_matches =
+            self.owner.as_ref().map_or(true, |owner| {
+                item.owner.as_ref() == Some(owner)
+            });
+        let state_matches = self
+            .state
+            .map_or(true, |state| item.state == state);
+        let name_matches = self
+            .name
+            .as_ref()
+            .map_or(true, |name| &item.name == name);
+        let finished_matches =
+            self.finished.map_or(true, |finished| {
+                item.is_finished() == finished
+            });
+        let washable_matches =
+            self.washable.map_or(true, |washable| {
+                item.washable == washable
+            });
+        let next_step_matches =
+            self.next_step.map_or(true, |step| {
+                item.next_step() == Some(step)
+            });
+
+        owner_matches
+            && state_matches
+            && name_matches
+            && finished_matches
+            && washable_matches
+            && next_step_matches
+    }
+}
+
+/// Moves the items held by `from` that match
+/// `filter` into the `Inventory` of `to`.
+///
+/// Only the items that fit in the volume left in
+/// `to` are moved. If `all_or_nothing` is set,
+/// nothing moves unless every matching item fits.
+/// Anything in the pockets of a moved item comes
+/// along without taking up space.
+///
+/// A `max_weight` stops filling `to` before it holds
+/// more than that many kilograms.
+#[derive(Event, Debug, Clone)]
+pub struct InventoryTransfer {
+    pub from: Entity,
+    pub to: Entity,
+    pub filter: ItemFilter,
+    pub all_or_nothing: bool,
+    pub max_weight: Option<f32>,
+}
+
+/// Sent whenever an `InventoryTransfer` moved at
+/// least one item.
+#[derive(Event, Debug, Clone)]
+pub struct ItemsTransferred {
+    pub from: Entity,
+    pub to: Entity,
+    pub items: Vec<Entity>,
+}
+
+fn transfer_items(
+    trigger: Trigger<InventoryTransfer>,
+    inventories: Query<&Inventory>,
+    mut items: Query<(
+        Entity,
+        &Item,
+        &mut HeldBy,
+        Option<&InPocket>,
+    )>,
+    mut transferred: EventWriter<ItemsTransferred>,
+) {
+    let InventoryTransfer {
+        from,
+        to,
+        filter,
+        all_or_nothing,
+        max_weight,
+    } = trigger.event();
+
+    if from == to {
+        return;
+    }
+
+    let Ok(destination) = inventories.get(*to) else {
+        warn!(?to, "transfer destination has no Inventory");
+        return;
+    };
+
+    let mut available_space =
+        destination.available_space(held_volume(
+            items.iter().map(
+                |(_, item, held_by, in_pocket)| {
+                    (item, held_by, in_pocket)
+                },
+            ),
+            *to,
+        ));
+
+    // decide on the full set of items to move before
+    // touching any of them, so a rejected transfer
+    // leaves both inventories untouched
+    let matching_items: Vec<(Entity, u32, f32)> = items
+        .iter()
+        .filter(|(_, item, held_by, in_pocket)| {
+            held_by.0 == *from
+                && in_pocket.is_none()
+                && filter.matches(item)
+        })
+        .map(|(entity, item, _, _)| {
+            // whatever is in the pockets weighs the
+            // item down
+            let pocket_weight: f32 = items
+                .iter()
+                .filter(|(_, _, _, in_pocket)| {
+                    in_pocket.is_some_and(|in_pocket| {
+                        in_pocket.0 == entity
+                    })
+                })
+                .map(|(_, item, _, _)| item.weight)
+                .sum();
+            (
+                entity,
+                item.volume,
+                item.weight + pocket_weight,
+            )
+        })
+        .collect();
+
+    let matching_volume: u32 = matching_items
+        .iter()
+        .map(|(_, volume, _)| volume)
+        .sum();
+    let matching_weight: f32 = matching_items
+        .iter()
+        .map(|(_, _, weight)| weight)
+        .sum();
+    let mut available_weight = max_weight.map(|max_weight| {
+        max_weight
+            - held_weight(
+                items.iter().map(|(_, item, held_by, _)| {
+                    (item, held_by)
+                }),
+                *to,
+            )
+    });
+    if *all_or_nothing
+        && (matching_volume > available_space
+            || available_weight.is_some_and(|available| {
+                matching_weight > available
+            }))
+    {
+        info!(
+            ?from,
+            ?to,
+            matching_volume,
+            available_space,
+            "not enough space for transfer"
+        );
+        return;
+    }
+
+    // a bulky item that doesn't fit shouldn't stop
+    // smaller ones behind it from being moved
+    let mut moved_items: Vec<Entity> = vec![];
+    for (entity, volume, weight) in matching_items {
+        if volume > available_space
+            || available_weight
+                .is_some_and(|available| weight > available)
+        {
+            continue;
+        }
+        available_space -= volume;
+        if let Some(available) = &mut available_weight {
+            *available -= weight;
+        }
+        moved_items.push(entity);
+    }
+
+    if moved_items.is_empty() {
+        return;
+    }
+
+    let pocket_contents: Vec<Entity> = items
+        .iter()
+        .filter(|(_, _, _, in_pocket)| {
+            in_pocket.is_some_and(|in_pocket| {
+                moved_items.contains(&in_pocket.0)
+            })
+        })
+        .map(|(entity, _, _, _)| entity)
+        .collect();
+    moved_items.extend(pocket_contents);
+
+    let mut moved_iter = items.iter_many_mut(&moved_items);
+    while let Some((_, _, mut held_by, _)) =
+        moved_iter.fetch_next()
+    {
+        held_by.0 = *to;
+    }
+
+    transferred.send(ItemsTransferred {
+        from: *from,
+        to: *to,
+        items: moved_items,
+    });
+}
+
+/// Takes everything out of the pockets of the items
+/// held by `holder`, leaving them as loose items in
+/// the same inventory.
+#[derive(Event, Debug, Clone)]
+pub struct CheckPockets {
+    pub holder: Entity,
+}
+
+/// Sent when `CheckPockets` found something.
+#[derive(Event, Debug, Clone)]
+pub struct PocketsEmptied {
+    pub holder: Entity,
+    pub items: Vec<Entity>,
+}
+
+fn check_pockets(
+    trigger: Trigger<CheckPockets>,
+    pocket_items: Query<(Entity, &HeldBy), With<InPocket>>,
+    mut emptied: EventWriter<PocketsEmptied>,
+    mut commands: Commands,
+) {
+    let holder = trigger.event().holder;
+    let found_items: Vec<Entity> = pocket_items
+        .iter()
+        .filter(|(_, held_by)| held_by.0 == holder)
+        .map(|(entity, _)| entity)
+        .collect();
+
+    if found_items.is_empty() {
+        return;
+    }
+
+    for entity in &found_items {
+        commands.entity(*entity).remove::<InPocket>();
+    }
+    info!(
+        ?holder,
+        count = found_items.len(),
+        "emptied pockets"
+    );
+    emptied.send(PocketsEmptied {
+        holder,
+        items: found_items,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shirt(owner: Option<PersistentId>) -> Item {
+        Item {
+            name: "Shirt".to_string(),
+            owner,
+            state: ProcessedState::Dirty,
+            order: vec![
+                ProcessingStep::Wash,
+                ProcessingStep::Dry,
+            ],
+            washable: true,
+            volume: 2,
+            weight: 0.3,
+            base_price: 300,
+            fabric: Fabric::Cotton,
+            color: LaundryColor::White,
+            condition: Item::DROPOFF_CONDITION,
+        }
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        let filter = ItemFilter::default();
+        assert!(filter.matches(&shirt(None)));
+    }
+
+    #[test]
+    fn filter_by_owner() {
+        let owner = PersistentId::new();
+        let filter = ItemFilter {
+            owner: Some(owner.clone()),
+            ..default()
+        };
+        assert!(filter.matches(&shirt(Some(owner))));
+        let stranger = Some(PersistentId::new());
+        assert!(!filter.matches(&shirt(stranger)));
+        assert!(!filter.matches(&shirt(None)));
+    }
+
+    #[test]
+    fn filter_by_progress() {
+        let mut item = shirt(None);
+        let wash_next = ItemFilter {
+            next_step: Some(ProcessingStep::Wash),
+            ..default()
+        };
+        let finished = ItemFilter {
+            finished: Some(true),
+            ..default()
+        };
+        assert!(wash_next.matches(&item));
+        assert!(!finished.matches(&item));
+
+        assert!(item.process(ProcessingStep::Wash));
+        assert!(item.process(ProcessingStep::Dry));
+        assert!(!wash_next.matches(&item));
+        assert!(finished.matches(&item));
+    }
+
+    #[test]
+    fn every_field_has_to_match() {
+        let filter = ItemFilter {
+            state: Some(ProcessedState::Dirty),
+            name: Some("Shirt".to_string()),
+            washable: Some(false),
+            ..default()
+        };
+        assert!(!filter.matches(&shirt(None)));
+
+        let filter = ItemFilter {
+            washable: Some(true),
+            ..filter
+        };
+        assert!(filter.matches(&shirt(None)));
+    }
+
+    fn setup() -> World {
+        let mut world = World::new();
+        world.init_resource::<Events<ItemsTransferred>>();
+        world.observe(transfer_items);
+        world
+    }
+
+    fn spawn_item(
+        world: &mut World,
+        holder: Entity,
+        volume: u32,
+        weight: f32,
+    ) -> Entity {
+        world
+            .spawn((
+                Item {
+                    volume,
+                    weight,
+                    ..shirt(None)
+                },
+                HeldBy(holder),
+            ))
+            .id()
+    }
+
+    fn transfer(
+        world: &mut World,
+        from: Entity,
+        to: Entity,
+        all_or_nothing: bool,
+        max_weight: Option<f32>,
+    ) {
+        world.trigger(InventoryTransfer {
+            from,
+            to,
+            filter: ItemFilter::default(),
+            all_or_nothing,
+            max_weight,
+        });
+        world.flush();
+    }
+
+    fn inventory(
+        world: &mut World,
+        max_volume: u32,
+    ) -> Entity {
+        world.spawn(Inventory { max_volume }).id()
+    }
+
+    fn holder(world: &World, item: Entity) -> Entity {
+        world.get::<HeldBy>(item).unwrap().0
+    }
+
+    fn transfers_sent(world: &World) -> usize {
+        world.resource::<Events<ItemsTransferred>>().len()
+    }
+
+    #[test]
+    fn bulky_items_dont_hold_up_smaller_ones() {
+        let mut world = setup();
+        let from = inventory(&mut world, 20);
+        let to = inventory(&mut world, 4);
+        let bulky = spawn_item(&mut world, from, 5, 1.);
+        let small = [
+            spawn_item(&mut world, from, 2, 0.3),
+            spawn_item(&mut world, from, 2, 0.3),
+        ];
+
+        transfer(&mut world, from, to, false, None);
+
+        assert_eq!(holder(&world, bulky), from);
+        for item in small {
+            assert_eq!(holder(&world, item), to);
+        }
+        assert_eq!(transfers_sent(&world), 1);
+    }
+
+    #[test]
+    fn pocket_contents_follow_their_item() {
+        let mut world = setup();
+        let from = inventory(&mut world, 20);
+        let to = inventory(&mut world, 2);
+        let jacket = spawn_item(&mut world, from, 2, 1.);
+        let pen = spawn_item(&mut world, from, 1, 0.1);
+        world.entity_mut(pen).insert(InPocket(jacket));
+
+        transfer(&mut world, from, to, false, None);
+
+        assert_eq!(holder(&world, jacket), to);
+        assert_eq!(holder(&world, pen), to);
+        let pocket = world.get::<InPocket>(pen).unwrap();
+        assert_eq!(pocket.0, jacket);
+    }
+
+    #[test]
+    fn max_weight_caps_what_moves() {
+        let mut world = setup();
+        let from = inventory(&mut world, 20);
+        let to = inventory(&mut world, 20);
+        spawn_item(&mut world, to, 1, 1.);
+        for _ in 0..3 {
+            spawn_item(&mut world, from, 1, 2.);
+        }
+
+        transfer(&mut world, from, to, false, Some(5.));
+
+        let mut held = world.query::<(&Item, &HeldBy)>();
+        let weight = held_weight(held.iter(&world), to);
+        assert_eq!(weight, 5.);
+    }
+
+    #[test]
+    fn transfer_to_self_does_nothing() {
+        let mut world = setup();
+        let basket = inventory(&mut world, 4);
+        let item = spawn_item(&mut world, basket, 2, 1.);
+
+        transfer(&mut world, basket, basket, false, None);
+
+        assert_eq!(holder(&world, item), basket);
+        assert_eq!(transfers_sent(&world), 0);
+    }
+
+    #[test]
+    fn all_or_nothing_moves_nothing_unless_all_fit() {
+        let mut world = setup();
+        let from = inventory(&mut world, 20);
+        let to = inventory(&mut world, 4);
+        let items = [
+            spawn_item(&mut world, from, 2, 0.3),
+            spawn_item(&mut world, from, 2, 0.3),
+            spawn_item(&mut world, from, 2, 0.3),
+        ];
+
+        transfer(&mut world, from, to, true, None);
+        for item in items {
+            assert_eq!(holder(&world, item), from);
+        }
+        assert_eq!(transfers_sent(&world), 0);
+
+        let mut destination =
+            world.get_mut::<Inventory>(to).unwrap();
+        destination.max_volume = 6;
+        transfer(&mut world, from, to, true, None);
+        for item in items {
+            assert_eq!(holder(&world, item), to);
+        }
     }
 }
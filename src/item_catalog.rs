@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::processing::{MachineProgram, ProcessingStep};
+
+pub struct ItemCatalogPlugin;
+
+impl Plugin for ItemCatalogPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Fabric>()
+            .register_type::<LaundryColor>()
+            .register_type::<ItemDefinition>();
+    }
+}
+
+/// Every kind of item a customer can bring in.
+///
+/// Loaded from `assets/data/laundry.items.ron` so
+/// new garments don't require touching Rust.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct ItemCatalog {
+    pub items: Vec<ItemDefinition>,
+}
+
+impl ItemCatalog {
+    /// Items customers bring in to be cleaned
+    pub fn garments(
+        &self,
+    ) -> impl Iterator<Item = &ItemDefinition> {
+        self.items.iter().filter(|item| item.washable)
+    }
+
+    /// Things customers forget in their pockets
+    pub fn pocket_items(
+        &self,
+    ) -> impl Iterator<Item = &ItemDefinition> {
+        self.items.iter().filter(|item| !item.washable)
+    }
+
+    pub fn get(
+        &self,
+        name: &str,
+    ) -> Option<&ItemDefinition> {
+        self.items.iter().find(|item| item.name == name)
+    }
+}
+
+#[derive(Debug, Reflect, Clone, Deserialize)]
+pub struct ItemDefinition {
+    pub name: String,
+    /// The processing stages this item can go
+    /// through. Optional pipeline stages listed here
+    /// are only done if the customer asks for them.
+    pub stages: Vec<ProcessingStep>,
+    pub fabric: Fabric,
+    /// The colors this item comes in. Each item a
+    /// customer brings in is one of these.
+    #[serde(default)]
+    pub colors: Vec<LaundryColor>,
+    /// in cents
+    pub base_price: u32,
+    /// How much room the item takes up in an
+    /// `Inventory`. A sock is 1.
+    #[serde(default = "default_volume")]
+    pub volume: u32,
+    /// in kilograms, machines can only handle so much
+    /// weight in one go
+    #[serde(default)]
+    pub weight: f32,
+    /// Anything that isn't washable ruins the wash
+    /// cycle it ends up in
+    #[serde(default = "default_washable")]
+    pub washable: bool,
+    /// Whether customers may leave something in the
+    /// pockets of this item
+    #[serde(default)]
+    pub has_pockets: bool,
+}
+
+impl ItemDefinition {
+    pub fn random_color(
+        &self,
+        rng: &mut impl Rng,
+    ) -> LaundryColor {
+        self.colors.choose(rng).copied().unwrap_or_default()
+    }
+}
+
+fn default_washable() -> bool {
+    true
+}
+
+fn default_volume() -> u32 {
+    1
+}
+
+#[derive(
+    Debug, Reflect, Clone, Copy, PartialEq, Eq, Deserialize,
+)]
+pub enum Fabric {
+    Cotton,
+    Denim,
+    Wool,
+    Silk,
+    Synthetic,
+    /// Things that aren't fabric at all, like the
+    /// pen someone forgot in their pocket
+    Other,
+}
+
+impl Fabric {
+    /// The harshest program this fabric survives
+    /// without losing quality
+    pub fn max_program(&self) -> MachineProgram {
+        match self {
+            Fabric::Silk | Fabric::Wool => {
+                MachineProgram::Delicate
+            }
+            Fabric::Synthetic | Fabric::Other => {
+                MachineProgram::Normal
+            }
+            Fabric::Cotton | Fabric::Denim => {
+                MachineProgram::Heavy
+            }
+        }
+    }
+
+    pub fn tolerates(
+        &self,
+        program: MachineProgram,
+    ) -> bool {
+        program <= self.max_program()
+    }
+}
+
+#[derive(
+    Debug,
+    Default,
+    Reflect,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Deserialize,
+)]
+pub enum LaundryColor {
+    #[default]
+    White,
+    Light,
+    Dark,
+    Red,
+}
+
+impl LaundryColor {
+    /// Whether dye runs out of this color in a wash
+    pub fn bleeds(&self) -> bool {
+        matches!(
+            self,
+            LaundryColor::Dark | LaundryColor::Red
+        )
+    }
+
+    /// Whether this color picks up dye from others
+    pub fn takes_dye(&self) -> bool {
+        matches!(
+            self,
+            LaundryColor::White | LaundryColor::Light
+        )
+    }
+}
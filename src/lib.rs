@@ -24,10 +24,19 @@ use bevy_mod_picking::{
 use bevy_picking_avian::AvianBackendSettings;
 use bevy_vello::render::VelloRenderSettings;
 use blenvy::BlenvyPlugin;
+use clock::ClockPlugin;
 use custom_materials::CustomMaterialsPlugin;
 use customer_npc::CustomerNpcPlugin;
+use day_cycle::DayCyclePlugin;
+use economy::EconomyPlugin;
 use inventory::InventoryPlugin;
+use item_catalog::ItemCatalogPlugin;
+use machine_shop::MachineShopPlugin;
 use persistent_id::PersistentIdPlugin;
+use processing::ProcessingPlugin;
+use rng::GameRngPlugin;
+use steering::SteeringPlugin;
+use utilities::UtilitiesPlugin;
 use woodpecker_ui::{RenderSettings, WoodpeckerUIPlugin};
 
 use crate::{
@@ -47,17 +56,26 @@ mod animation_graph_processing;
 mod assets;
 mod blenvy_helpers;
 mod camera;
+mod clock;
 pub mod collision_layers;
 mod controls;
 mod custom_materials;
 mod customer_npc;
+mod day_cycle;
+mod economy;
 mod game_scene;
 mod grid;
 mod inventory;
+mod item_catalog;
+mod machine_shop;
 mod main_menu;
 mod navmesh;
 mod persistent_id;
+mod processing;
+mod rng;
 mod states;
+mod steering;
+mod utilities;
 mod widgets;
 
 pub struct AppPlugin;
@@ -124,9 +142,20 @@ impl Plugin for AppPlugin {
                 widgets::WashCycleWidgetsPlugin,
                 AnimationGraphProcessingPlugin,
                 PersistentIdPlugin,
-                InventoryPlugin,
+                GameRngPlugin,
+                SteeringPlugin,
                 CustomMaterialsPlugin,
             ),
+            (
+                InventoryPlugin,
+                ProcessingPlugin,
+                ItemCatalogPlugin,
+                ClockPlugin,
+                EconomyPlugin,
+                MachineShopPlugin,
+                UtilitiesPlugin,
+                DayCyclePlugin,
+            ),
         ))
         // .insert_resource(DebugPickingMode::Normal)
         .add_systems(
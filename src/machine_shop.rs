@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    processing::ProcessingStep, utilities::CycleUsage,
+};
+
+pub struct MachineShopPlugin;
+
+impl Plugin for MachineShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<MachineListing>()
+            .register_type::<MachineUpgrade>()
+            .register_type::<PurchasePrice>()
+            .register_type::<MachineTier>();
+    }
+}
+
+/// Share of the purchase price the shop gets back
+/// when a machine is sold
+const RESALE_SHARE: f32 = 0.5;
+
+/// The machines the shop can buy.
+///
+/// Loaded from `assets/data/machines.shop.ron`.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct MachineCatalog {
+    pub machines: Vec<MachineListing>,
+}
+
+impl MachineCatalog {
+    pub fn listing(
+        &self,
+        step: ProcessingStep,
+    ) -> Option<&MachineListing> {
+        self.machines
+            .iter()
+            .find(|listing| listing.step == step)
+    }
+}
+
+#[derive(Debug, Reflect, Clone, Deserialize)]
+pub struct MachineListing {
+    pub step: ProcessingStep,
+    /// Path of the blenvy blueprint to spawn
+    pub blueprint: String,
+    /// in cents
+    pub price: u32,
+    /// What every cycle of the machine uses up
+    pub usage: CycleUsage,
+    /// Upgrades in the order they can be bought
+    #[serde(default)]
+    pub upgrades: Vec<MachineUpgrade>,
+}
+
+impl MachineListing {
+    /// The upgrade a machine at `tier` can buy next
+    pub fn next_upgrade(
+        &self,
+        tier: MachineTier,
+    ) -> Option<&MachineUpgrade> {
+        self.upgrades.get(tier.0)
+    }
+}
+
+/// A better version of a machine. Its numbers replace
+/// the ones the machine had before.
+#[derive(Debug, Reflect, Clone, Deserialize)]
+pub struct MachineUpgrade {
+    pub name: String,
+    /// in cents
+    pub price: u32,
+    /// The blueprint variant the machine turns into
+    pub blueprint: String,
+    /// in kilograms
+    pub max_load: f32,
+    /// How long a cycle takes, in seconds
+    pub cycle_seconds: f32,
+    /// Extra condition items get from every step they
+    /// needed
+    pub quality_bonus: f32,
+}
+
+/// How many upgrades a machine has had.
+#[derive(
+    Component, Reflect, Debug, Clone, Copy, Default,
+)]
+#[reflect(Component)]
+pub struct MachineTier(pub usize);
+
+/// What the shop paid for a machine and its
+/// upgrades, in cents.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct PurchasePrice(pub u32);
+
+impl PurchasePrice {
+    /// What selling the machine back brings in, in
+    /// cents
+    pub fn resale_value(&self) -> u32 {
+        (self.0 as f32 * RESALE_SHARE).round() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resale_value_is_a_share_of_the_price() {
+        assert_eq!(PurchasePrice(1000).resale_value(), 500);
+        assert_eq!(PurchasePrice(0).resale_value(), 0);
+    }
+
+    #[test]
+    fn resale_value_rounds_to_whole_cents() {
+        assert_eq!(PurchasePrice(333).resale_value(), 167);
+    }
+}
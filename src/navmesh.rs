@@ -14,7 +14,7 @@ use bevy::{
 };
 use blenvy::{BlueprintInfo, SpawnBlueprint};
 use geo::{LineString, Polygon as GeoPolygon};
-use rand::{rngs::ThreadRng, Rng};
+use rand::Rng;
 use vleue_navigator::{
     prelude::*, NavMeshDebug, Triangulation,
 };
@@ -22,7 +22,11 @@ use vleue_navigator::{
 use crate::{
     assets::NavMeshAssets,
     customer_npc::CustomerNpc,
+    rng::{GameRng, RngStream},
     states::{AppState, IsPaused},
+    steering::{
+        waypoint_reached, Crowd, Steering, SteeringSystems,
+    },
 };
 
 pub struct NavMeshPlugin;
@@ -47,7 +51,8 @@ impl Plugin for NavMeshPlugin {
             (
                 give_target_auto,
                 trigger_navmesh_visibility,
-                move_object,
+                plan_paths,
+                move_object.after(SteeringSystems),
             )
                 .run_if(in_state(IsPaused::Running)),
         )
@@ -80,6 +85,15 @@ pub struct Path {
     pub next: Vec<Vec3>,
 }
 
+/// Where an entity wants to end up.
+///
+/// `plan_paths` turns this into a `Path` along the
+/// navmesh, and plans a new one for anything still on
+/// its way whenever the navmesh is rebuilt, like
+/// after a machine is placed.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Destination(pub Vec3);
+
 // pub fn from_outer_edges(edges: &[Vec2]) ->
 // Triangulation {     Triangulation {
 //         inner: GeoPolygon::new(
@@ -204,6 +218,8 @@ fn setup_navmesh(
                 ..Default::default()
             },
             Object(None),
+            Crowd { radius: 0.5 },
+            Steering::new(10.0),
             NotShadowCaster,
         ))
         .with_children(|object| {
@@ -233,7 +249,9 @@ fn give_target_auto(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     current_mesh: Res<CurrentMesh>,
+    mut game_rng: ResMut<GameRng>,
 ) {
+    let rng = game_rng.stream(RngStream::Navigation);
     for (entity, transform, mut object) in
         object_query.iter_mut()
     {
@@ -242,8 +260,8 @@ fn give_target_auto(
         let mut x;
         let mut z;
         loop {
-            x = rand::thread_rng().gen_range(-10.0..10.0);
-            z = rand::thread_rng().gen_range(-10.0..10.0);
+            x = rng.gen_range(-10.0..10.0);
+            z = rng.gen_range(-10.0..10.0);
 
             if navmesh.transformed_is_in_mesh(Vec3::new(
                 x, 0.0, z,
@@ -313,6 +331,75 @@ fn give_target_auto(
     }
 }
 
+fn plan_paths(
+    mut commands: Commands,
+    travelers: Query<(
+        Entity,
+        &Transform,
+        Ref<Destination>,
+        Has<Path>,
+    )>,
+    navmeshes: Res<Assets<NavMesh>>,
+    current_mesh: Option<Res<CurrentMesh>>,
+    mut navmesh_events: EventReader<AssetEvent<NavMesh>>,
+) {
+    let navmesh =
+        current_mesh.as_ref().and_then(|current_mesh| {
+            navmeshes.get(&current_mesh.0)
+        });
+    let rebuilt =
+        current_mesh.as_ref().is_some_and(|current_mesh| {
+            navmesh_events.read().any(|event| {
+                event.is_modified(&current_mesh.0)
+                    || event.is_added(&current_mesh.0)
+            })
+        });
+
+    for (entity, transform, destination, has_path) in
+        &travelers
+    {
+        if !destination.is_changed()
+            && !(rebuilt && has_path)
+        {
+            continue;
+        }
+
+        // keep walking at the height of the destination,
+        // the navmesh only cares about the floor plan
+        let waypoints = navmesh
+            .and_then(|navmesh| {
+                navmesh.transformed_path(
+                    transform.translation,
+                    destination.0,
+                )
+            })
+            .map(|path| {
+                path.path
+                    .iter()
+                    .map(|point| point.with_y(destination.0.y))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_else(|| {
+                warn_once!(
+                    "no path on the navmesh, walking straight"
+                );
+                vec![destination.0]
+            });
+
+        let Some((first, remaining)) =
+            waypoints.split_first()
+        else {
+            continue;
+        };
+        let mut remaining = remaining.to_vec();
+        remaining.reverse();
+        commands.entity(entity).insert(Path {
+            current: *first,
+            next: remaining,
+        });
+    }
+}
+
 fn trigger_navmesh_visibility(
     mut query: Query<(&mut Visibility, &NavMeshDisp)>,
     keyboard_input: ResMut<ButtonInput<KeyCode>>,
@@ -345,22 +432,24 @@ fn move_object(
             &mut Path,
             Entity,
             &mut Object,
+            &Steering,
         ),
         Without<CustomerNpc>,
     >,
     time: Res<Time>,
 ) {
-    for (mut transform, mut target, entity, mut object) in
-        object_query.iter_mut()
+    for (
+        mut transform,
+        mut target,
+        entity,
+        mut object,
+        steering,
+    ) in object_query.iter_mut()
     {
-        let move_direction =
-            target.current - transform.translation;
-        transform.translation += move_direction.normalize()
-            * time.delta_seconds()
-            * 10.0;
-
-        if transform.translation.distance(target.current)
-            < 0.1
+        transform.translation +=
+            steering.velocity * time.delta_seconds();
+
+        if waypoint_reached(&target, transform.translation)
         {
             if let Some(next) = target.next.pop() {
                 target.current = next;
@@ -384,10 +473,11 @@ fn spawn_obstacle(
     camera_q: Query<(&Camera, &GlobalTransform)>,
     mut commands: Commands,
     settings: Query<Ref<NavMeshSettings>>,
+    mut game_rng: ResMut<GameRng>,
 ) {
-    let mut rng = rand::thread_rng();
-    let x = rand::thread_rng().gen_range(-10.0..10.0);
-    let z = rand::thread_rng().gen_range(-10.0..10.0);
+    let rng = game_rng.stream(RngStream::Navigation);
+    let x = rng.gen_range(-10.0..10.0);
+    let z = rng.gen_range(-10.0..10.0);
 
     commands.spawn((
         Obstacle,
@@ -0,0 +1,299 @@
+use bevy::{color::palettes::tailwind::*, prelude::*};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::inventory::{Item, ProcessedState};
+
+pub struct ProcessingPlugin;
+
+impl Plugin for ProcessingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ProcessingStep>()
+            .register_type::<MachineProgram>()
+            .register_type::<LaundryPipeline>()
+            .init_resource::<LaundryPipeline>();
+    }
+}
+
+/// A single thing a machine can do to an item.
+///
+/// Each step moves an item into the
+/// `ProcessedState` returned by `result`.
+#[derive(
+    Debug,
+    Reflect,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Deserialize,
+)]
+pub enum ProcessingStep {
+    Wash,
+    Dry,
+    Press,
+    Fold,
+}
+
+impl ProcessingStep {
+    pub const ALL: [Self; 4] =
+        [Self::Wash, Self::Dry, Self::Press, Self::Fold];
+
+    pub fn result(&self) -> ProcessedState {
+        match self {
+            ProcessingStep::Wash => ProcessedState::Washed,
+            ProcessingStep::Dry => ProcessedState::Dried,
+            ProcessingStep::Press => {
+                ProcessedState::Pressed
+            }
+            ProcessingStep::Fold => ProcessedState::Folded,
+        }
+    }
+
+    /// The most weight, in kilograms, the machine for
+    /// this step handles in one go
+    pub fn max_load(&self) -> f32 {
+        match self {
+            ProcessingStep::Wash => 5.,
+            ProcessingStep::Dry => 6.,
+            ProcessingStep::Press => 2.,
+            ProcessingStep::Fold => 8.,
+        }
+    }
+
+    /// What a self-service customer pays, in cents, to
+    /// run the machine for this step once
+    pub fn self_service_price(&self) -> u32 {
+        match self {
+            ProcessingStep::Wash => 400,
+            ProcessingStep::Dry => 300,
+            ProcessingStep::Press => 200,
+            ProcessingStep::Fold => 100,
+        }
+    }
+
+    pub fn machine_name(&self) -> &'static str {
+        match self {
+            ProcessingStep::Wash => "Washer",
+            ProcessingStep::Dry => "Dryer",
+            ProcessingStep::Press => "Press",
+            ProcessingStep::Fold => "Folding Table",
+        }
+    }
+
+    /// The lamp on top of a machine, so machines for
+    /// different steps can be told apart
+    pub fn machine_color(&self) -> Srgba {
+        match self {
+            ProcessingStep::Wash => SKY_500,
+            ProcessingStep::Dry => ORANGE_500,
+            ProcessingStep::Press => VIOLET_500,
+            ProcessingStep::Fold => EMERALD_500,
+        }
+    }
+}
+
+#[derive(Debug, Reflect, Clone)]
+pub struct PipelineStage {
+    pub step: ProcessingStep,
+    /// optional stages are only done for customers
+    /// who ask for them
+    pub optional: bool,
+}
+
+/// The order laundry moves through the shop.
+///
+/// Customers order a subset of these stages, always
+/// in this order.
+#[derive(Resource, Debug, Reflect, Clone)]
+#[reflect(Resource)]
+pub struct LaundryPipeline {
+    pub stages: Vec<PipelineStage>,
+}
+
+impl Default for LaundryPipeline {
+    fn default() -> Self {
+        Self {
+            stages: vec![
+                PipelineStage {
+                    step: ProcessingStep::Wash,
+                    optional: false,
+                },
+                PipelineStage {
+                    step: ProcessingStep::Dry,
+                    optional: false,
+                },
+                PipelineStage {
+                    step: ProcessingStep::Press,
+                    optional: true,
+                },
+                PipelineStage {
+                    step: ProcessingStep::Fold,
+                    optional: false,
+                },
+            ],
+        }
+    }
+}
+
+impl LaundryPipeline {
+    /// Builds an order out of the `stages` an item
+    /// supports. Required stages are always included,
+    /// optional ones only when `include_optional`
+    /// returns true.
+    pub fn order(
+        &self,
+        stages: &[ProcessingStep],
+        mut include_optional: impl FnMut(ProcessingStep) -> bool,
+    ) -> Vec<ProcessingStep> {
+        self.stages
+            .iter()
+            .filter(|stage| stages.contains(&stage.step))
+            .filter(|stage| {
+                !stage.optional
+                    || include_optional(stage.step)
+            })
+            .map(|stage| stage.step)
+            .collect()
+    }
+}
+
+/// How rough a machine is on the items in it.
+#[derive(
+    Component,
+    Debug,
+    Default,
+    Reflect,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[reflect(Component)]
+pub enum MachineProgram {
+    Delicate,
+    #[default]
+    Normal,
+    Heavy,
+}
+
+impl MachineProgram {
+    pub fn next(&self) -> Self {
+        match self {
+            MachineProgram::Delicate => {
+                MachineProgram::Normal
+            }
+            MachineProgram::Normal => MachineProgram::Heavy,
+            MachineProgram::Heavy => {
+                MachineProgram::Delicate
+            }
+        }
+    }
+}
+
+/// Chance that a light item picks up dye from a
+/// load with something that bleeds in it
+const COLOR_BLEED_CHANCE: f64 = 0.5;
+const COLOR_BLEED_CONDITION_LOSS: f32 = 0.3;
+/// Chance that a fabric is damaged by a program
+/// harsher than it can take
+const FABRIC_DAMAGE_CHANCE: f64 = 0.7;
+const FABRIC_DAMAGE_CONDITION_LOSS: f32 = 0.4;
+/// Drying something that is already dry
+const OVER_DRY_CONDITION_LOSS: f32 = 0.15;
+/// Washing something with a stain-causing item
+pub const STAIN_CONDITION_LOSS: f32 = 0.5;
+
+impl ProcessingStep {
+    /// How much doing this step well improves an item
+    fn condition_gain(&self) -> f32 {
+        match self {
+            ProcessingStep::Wash => 0.1,
+            ProcessingStep::Dry => 0.,
+            ProcessingStep::Press => 0.15,
+            ProcessingStep::Fold => 0.05,
+        }
+    }
+}
+
+/// How much an item's condition changes when a
+/// machine running `program` does `step` to it.
+///
+/// `needed` is whether `step` was the next step in
+/// the item's order, `load_bleeds` whether anything
+/// else in the load bleeds dye.
+pub fn condition_change(
+    item: &Item,
+    step: ProcessingStep,
+    needed: bool,
+    program: MachineProgram,
+    load_bleeds: bool,
+    rng: &mut impl Rng,
+) -> f32 {
+    let mut change =
+        if needed { step.condition_gain() } else { 0. };
+
+    if step == ProcessingStep::Wash
+        && load_bleeds
+        && item.color.takes_dye()
+        && rng.gen_bool(COLOR_BLEED_CHANCE)
+    {
+        change -= COLOR_BLEED_CONDITION_LOSS;
+    }
+    // folding is done by hand, everything else can
+    // be too rough for delicate fabrics
+    if step != ProcessingStep::Fold
+        && !item.fabric.tolerates(program)
+        && rng.gen_bool(FABRIC_DAMAGE_CHANCE)
+    {
+        change -= FABRIC_DAMAGE_CONDITION_LOSS;
+    }
+    if step == ProcessingStep::Dry
+        && !needed
+        && item.state >= ProcessedState::Dried
+    {
+        change -= OVER_DRY_CONDITION_LOSS;
+    }
+    change
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ProcessingStep::*;
+
+    #[test]
+    fn order_follows_the_pipeline() {
+        let pipeline = LaundryPipeline::default();
+        let order =
+            pipeline.order(&[Fold, Press, Dry, Wash], |_| {
+                true
+            });
+        assert_eq!(order, vec![Wash, Dry, Press, Fold]);
+    }
+
+    #[test]
+    fn optional_stages_only_when_asked_for() {
+        let pipeline = LaundryPipeline::default();
+        let mut asked = vec![];
+        let order =
+            pipeline.order(&ProcessingStep::ALL, |step| {
+                asked.push(step);
+                false
+            });
+        assert_eq!(order, vec![Wash, Dry, Fold]);
+        assert_eq!(asked, vec![Press]);
+    }
+
+    #[test]
+    fn order_skips_unsupported_stages() {
+        let pipeline = LaundryPipeline::default();
+        let order =
+            pipeline.order(&[Wash, Press], |_| true);
+        assert_eq!(order, vec![Wash, Press]);
+    }
+}
@@ -0,0 +1,196 @@
+use bevy::{prelude::*, utils::HashMap};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::states::AppState;
+
+/// Seeds all gameplay randomness so a shift can be
+/// replayed exactly.
+///
+/// The seed comes from `--seed <n>` on the command
+/// line, then the `WASH_CYCLE_SEED` environment
+/// variable, and is random otherwise. Either way it
+/// is logged so it can be put in a bug report.
+pub struct GameRngPlugin;
+
+impl Plugin for GameRngPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = seed_from_args()
+            .or_else(seed_from_env)
+            .unwrap_or_else(rand::random);
+        info!(seed, "gameplay rng seed");
+
+        app.insert_resource(GameRng::new(seed))
+            .add_systems(
+                OnEnter(AppState::InGame),
+                reseed_game_rng,
+            );
+    }
+}
+
+/// A separate stream of random numbers for each part
+/// of the game, so that rolling more dice in one
+/// place doesn't change what happens in another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RngStream {
+    /// When and where customers show up
+    Spawning,
+    /// Who the customers are and what they bring
+    Customers,
+    /// Wandering targets and obstacle placement
+    Navigation,
+    /// What happens to items in machines
+    Processing,
+}
+
+impl RngStream {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RngStream::Spawning => "spawning",
+            RngStream::Customers => "customers",
+            RngStream::Navigation => "navigation",
+            RngStream::Processing => "processing",
+        }
+    }
+
+    /// Derives the seed for this stream from the game
+    /// seed. FNV-1a over the stream name, so it doesn't
+    /// depend on the order streams are first used in.
+    fn seed(&self, game_seed: u64) -> u64 {
+        self.name().bytes().fold(
+            0xcbf29ce484222325,
+            |hash, byte| {
+                (hash ^ byte as u64)
+                    .wrapping_mul(0x100000001b3)
+            },
+        ) ^ game_seed
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct GameRng {
+    seed: u64,
+    streams: HashMap<RngStream, StdRng>,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: HashMap::default(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Starts every stream over from the beginning
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.streams.clear();
+    }
+
+    pub fn stream(
+        &mut self,
+        stream: RngStream,
+    ) -> &mut StdRng {
+        let seed = self.seed;
+        self.streams.entry(stream).or_insert_with(|| {
+            StdRng::seed_from_u64(stream.seed(seed))
+        })
+    }
+}
+
+fn seed_from_args() -> Option<u64> {
+    let mut args = std::env::args();
+    args.find(|arg| arg == "--seed")?;
+    let seed = args.next()?;
+    seed.parse()
+        .inspect_err(|error| {
+            warn!(?error, %seed, "invalid --seed");
+        })
+        .ok()
+}
+
+fn seed_from_env() -> Option<u64> {
+    let seed = std::env::var("WASH_CYCLE_SEED").ok()?;
+    seed.parse()
+        .inspect_err(|error| {
+            warn!(?error, %seed, "invalid WASH_CYCLE_SEED");
+        })
+        .ok()
+}
+
+/// Every shift starts from the same seed
+fn reseed_game_rng(mut game_rng: ResMut<GameRng>) {
+    let seed = game_rng.seed();
+    game_rng.reseed(seed);
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn streams_ignore_the_order_they_are_drawn_in() {
+        let mut first = GameRng::new(42);
+        let mut second = GameRng::new(42);
+
+        let mut first_spawning = vec![];
+        let mut first_processing = vec![];
+        for _ in 0..8 {
+            first_spawning.push(
+                first
+                    .stream(RngStream::Spawning)
+                    .r#gen::<u64>(),
+            );
+            first_processing.push(
+                first
+                    .stream(RngStream::Processing)
+                    .r#gen::<u64>(),
+            );
+        }
+
+        // all of one stream, then all of the other
+        let second_processing: Vec<u64> = (0..8)
+            .map(|_| {
+                second
+                    .stream(RngStream::Processing)
+                    .r#gen()
+            })
+            .collect();
+        let second_spawning: Vec<u64> = (0..8)
+            .map(|_| {
+                second.stream(RngStream::Spawning).r#gen()
+            })
+            .collect();
+
+        assert_eq!(first_spawning, second_spawning);
+        assert_eq!(first_processing, second_processing);
+        assert_ne!(first_spawning, first_processing);
+    }
+
+    #[test]
+    fn reseed_starts_the_streams_over() {
+        let mut game_rng = GameRng::new(7);
+        let before: u64 =
+            game_rng.stream(RngStream::Customers).r#gen();
+        game_rng.reseed(7);
+        let after: u64 =
+            game_rng.stream(RngStream::Customers).r#gen();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        let mut first = GameRng::new(1);
+        let mut second = GameRng::new(2);
+        let first: u64 =
+            first.stream(RngStream::Navigation).r#gen();
+        let second: u64 =
+            second.stream(RngStream::Navigation).r#gen();
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,234 @@
+use avian3d::prelude::LinearVelocity;
+use bevy::prelude::*;
+
+use crate::{navmesh::Path, states::IsPaused};
+
+pub struct SteeringPlugin;
+
+impl Plugin for SteeringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            steer
+                .in_set(SteeringSystems)
+                .run_if(in_state(IsPaused::Running)),
+        );
+    }
+}
+
+/// Anything that moves an entity by its
+/// `Steering::velocity` runs after this.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SteeringSystems;
+
+/// Takes up room. Steering agents walk around
+/// anything in the crowd, including the player.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Crowd {
+    pub radius: f32,
+}
+
+/// Walks along a `Path`, slowing down on arrival and
+/// stepping around the rest of the `Crowd`.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Steering {
+    pub max_speed: f32,
+    /// How the agent is moving this frame
+    pub velocity: Vec3,
+}
+
+impl Steering {
+    pub fn new(max_speed: f32) -> Self {
+        Self {
+            max_speed,
+            velocity: Vec3::ZERO,
+        }
+    }
+}
+
+/// How close to the last waypoint counts as arrived
+const ARRIVAL_RADIUS: f32 = 0.1;
+/// Corners along the way can be cut a little
+const WAYPOINT_RADIUS: f32 = 0.5;
+/// Agents start slowing down this far from the end
+const SLOWING_DISTANCE: f32 = 1.5;
+/// Fraction of `max_speed` agents keep while
+/// slowing down, so they actually get there
+const MIN_ARRIVAL_SPEED: f32 = 0.2;
+/// Seconds ahead agents look for someone to avoid
+const AVOIDANCE_HORIZON: f32 = 1.5;
+/// How hard overlapping agents push apart
+const SEPARATION_STRENGTH: f32 = 4.0;
+/// How quickly agents reach the velocity they want
+const ACCELERATION: f32 = 8.0;
+
+/// Whether `position` is close enough to the current
+/// waypoint to move on to the next one.
+pub fn waypoint_reached(
+    path: &Path,
+    position: Vec3,
+) -> bool {
+    let radius = if path.next.is_empty() {
+        ARRIVAL_RADIUS
+    } else {
+        WAYPOINT_RADIUS
+    };
+    position.distance(path.current) < radius
+}
+
+struct Neighbor {
+    entity: Entity,
+    position: Vec3,
+    velocity: Vec3,
+    radius: f32,
+    steers: bool,
+}
+
+fn steer(
+    time: Res<Time>,
+    mut crowd: Query<(
+        Entity,
+        &Transform,
+        &Crowd,
+        Option<&mut Steering>,
+        Option<&Path>,
+        Option<&LinearVelocity>,
+    )>,
+) {
+    let neighbors = crowd
+        .iter()
+        .map(
+            |(
+                entity,
+                transform,
+                member,
+                steering,
+                _,
+                linear_velocity,
+            )| Neighbor {
+                entity,
+                position: transform.translation,
+                velocity: steering
+                    .map(|steering| steering.velocity)
+                    .or(linear_velocity.map(|v| v.0))
+                    .unwrap_or_default(),
+                radius: member.radius,
+                steers: steering.is_some(),
+            },
+        )
+        .collect::<Vec<_>>();
+
+    let dt = time.delta_seconds();
+    for (entity, transform, member, steering, path, _) in
+        &mut crowd
+    {
+        let Some(mut steering) = steering else {
+            continue;
+        };
+        let Some(path) = path else {
+            steering.velocity = Vec3::ZERO;
+            continue;
+        };
+
+        let position = transform.translation;
+        let to_waypoint = path.current - position;
+        let distance = to_waypoint.length();
+        let mut speed = steering.max_speed;
+        if path.next.is_empty() {
+            speed *= (distance / SLOWING_DISTANCE)
+                .clamp(MIN_ARRIVAL_SPEED, 1.0);
+            // don't step past the end
+            if dt > 0. {
+                speed = speed.min(distance / dt);
+            }
+        }
+
+        let mut desired =
+            to_waypoint.normalize_or_zero() * speed;
+        for neighbor in &neighbors {
+            if neighbor.entity == entity {
+                continue;
+            }
+            desired += avoidance(
+                entity,
+                position,
+                steering.velocity,
+                member.radius,
+                neighbor,
+            );
+        }
+
+        let desired =
+            desired.clamp_length_max(steering.max_speed);
+        let blend = (ACCELERATION * dt).min(1.);
+        steering.velocity =
+            steering.velocity.lerp(desired, blend);
+    }
+}
+
+/// Pushes an agent away from `neighbor`: apart when
+/// they already overlap, and sideways when they are
+/// about to walk into each other.
+fn avoidance(
+    entity: Entity,
+    position: Vec3,
+    velocity: Vec3,
+    radius: f32,
+    neighbor: &Neighbor,
+) -> Vec3 {
+    let offset = (neighbor.position - position).with_y(0.);
+    let distance = offset.length();
+    let min_distance = radius + neighbor.radius;
+    let mut push = Vec3::ZERO;
+
+    if distance < min_distance {
+        // two agents on the same spot still need to
+        // pick opposite directions
+        let away = if distance > f32::EPSILON {
+            -offset / distance
+        } else if entity < neighbor.entity {
+            Vec3::X
+        } else {
+            Vec3::NEG_X
+        };
+        push += away
+            * SEPARATION_STRENGTH
+            * (min_distance - distance)
+            / min_distance;
+    }
+
+    // agents that steer take half of the dodge each,
+    // everyone else leaves it all to us
+    let share = if neighbor.steers { 0.5 } else { 1.0 };
+    let relative_velocity =
+        (velocity - neighbor.velocity).with_y(0.);
+    let closing_speed = relative_velocity.length_squared();
+    if closing_speed < f32::EPSILON {
+        return push;
+    }
+    let time_to_closest =
+        offset.dot(relative_velocity) / closing_speed;
+    if time_to_closest <= 0.
+        || time_to_closest > AVOIDANCE_HORIZON
+    {
+        return push;
+    }
+    let closest =
+        offset - relative_velocity * time_to_closest;
+    let miss = closest.length();
+    if miss >= min_distance {
+        return push;
+    }
+    let side = if miss > f32::EPSILON {
+        -closest / miss
+    } else {
+        Vec3::new(
+            -relative_velocity.z,
+            0.,
+            relative_velocity.x,
+        )
+        .normalize()
+    };
+    push + side * share * (min_distance - miss)
+        / time_to_closest
+}
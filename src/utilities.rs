@@ -0,0 +1,318 @@
+use avian3d::prelude::Collider;
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    clock::{DayEnded, GameClock},
+    customer_npc::CustomerNpcSpawner,
+    economy::{Transaction, TransactionKind, Wallet},
+    game_scene::{InvalidRangeToObject, Player},
+    processing::MachineProgram,
+    states::{AppState, IsPaused},
+};
+
+pub struct UtilitiesPlugin;
+
+impl Plugin for UtilitiesPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<CycleUsage>()
+            .register_type::<UtilityMeter>()
+            .register_type::<DetergentStock>()
+            .register_type::<SupplyDelivery>()
+            .init_resource::<UtilityMeter>()
+            .init_resource::<DetergentStock>()
+            .observe(order_detergent)
+            .observe(bill_utilities)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                reset_utilities,
+            )
+            .add_systems(
+                Update,
+                deliver_supplies
+                    .run_if(in_state(IsPaused::Running)),
+            );
+    }
+}
+
+/// in cents
+const WATER_PRICE_PER_LITER: f32 = 0.5;
+/// in cents
+const POWER_PRICE_PER_KWH: f32 = 30.;
+/// Doses the shop opens with
+const STARTING_DETERGENT: u32 = 20;
+/// Doses in every case of detergent
+const DETERGENT_CASE_DOSES: u32 = 40;
+/// in cents
+const DETERGENT_CASE_PRICE: u32 = 2000;
+/// In-game minutes between ordering detergent and it
+/// showing up
+const DELIVERY_LEAD_TIME: f32 = 60.;
+/// How close the player has to be to carry a
+/// delivered case inside
+const SUPPLY_REACH: f32 = 2.5;
+
+/// What one cycle of a machine uses up on its
+/// `MachineProgram::Normal` program.
+#[derive(
+    Component,
+    Reflect,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    Deserialize,
+)]
+#[reflect(Component)]
+pub struct CycleUsage {
+    /// in liters
+    pub water: f32,
+    /// in kilowatt hours
+    pub power: f32,
+    /// in doses
+    #[serde(default)]
+    pub detergent: u32,
+}
+
+impl CycleUsage {
+    /// Gentler programs use less of everything,
+    /// heavier ones more
+    pub fn for_program(
+        &self,
+        program: MachineProgram,
+    ) -> CycleUsage {
+        let scale = match program {
+            MachineProgram::Delicate => 0.75,
+            MachineProgram::Normal => 1.,
+            MachineProgram::Heavy => 1.5,
+        };
+        CycleUsage {
+            water: self.water * scale,
+            power: self.power * scale,
+            detergent: (self.detergent as f32 * scale)
+                .ceil() as u32,
+        }
+    }
+}
+
+/// Water and power used since the last bill.
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct UtilityMeter {
+    /// in liters
+    pub water: f32,
+    /// in kilowatt hours
+    pub power: f32,
+}
+
+impl UtilityMeter {
+    /// What the utility company charges for the
+    /// usage so far, in cents
+    pub fn bill(&self) -> u32 {
+        (self.water * WATER_PRICE_PER_LITER
+            + self.power * POWER_PRICE_PER_KWH)
+            .round() as u32
+    }
+}
+
+/// Detergent on the shelf. Washers won't start
+/// without enough of it.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct DetergentStock {
+    /// in doses
+    pub doses: u32,
+}
+
+impl Default for DetergentStock {
+    fn default() -> Self {
+        Self {
+            doses: STARTING_DETERGENT,
+        }
+    }
+}
+
+impl DetergentStock {
+    /// Whether there's enough on the shelf for a cycle
+    pub fn covers(&self, usage: CycleUsage) -> bool {
+        usage.detergent <= self.doses
+    }
+}
+
+/// Buy a case of detergent. It arrives at the door a
+/// while later.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct OrderDetergent;
+
+/// A case of detergent on its way, or waiting at the
+/// door once it has `arrived`.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct SupplyDelivery {
+    pub doses: u32,
+    /// The `GameClock::elapsed_minutes` it shows up at
+    pub arrives_at: f32,
+    pub arrived: bool,
+}
+
+/// Takes what a cycle uses out of stock and onto the
+/// meter. Returns `false` without using anything if
+/// there isn't enough detergent.
+pub fn use_supplies(
+    usage: CycleUsage,
+    stock: &mut DetergentStock,
+    meter: &mut UtilityMeter,
+) -> bool {
+    if !stock.covers(usage) {
+        return false;
+    }
+    stock.doses -= usage.detergent;
+    meter.water += usage.water;
+    meter.power += usage.power;
+    true
+}
+
+fn reset_utilities(mut commands: Commands) {
+    commands.insert_resource(UtilityMeter::default());
+    commands.insert_resource(DetergentStock::default());
+}
+
+fn order_detergent(
+    _trigger: Trigger<OrderDetergent>,
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    wallet: Res<Wallet>,
+) {
+    if !wallet.can_afford(DETERGENT_CASE_PRICE) {
+        info!(
+            price = DETERGENT_CASE_PRICE,
+            balance = wallet.balance(),
+            "can't afford detergent"
+        );
+        return;
+    }
+    commands.trigger(Transaction {
+        kind: TransactionKind::SupplyPurchase,
+        amount: DETERGENT_CASE_PRICE,
+        memo: "case of detergent".to_string(),
+    });
+    let arrives_at =
+        clock.elapsed_minutes() + DELIVERY_LEAD_TIME;
+    info!(arrives_at, "detergent ordered");
+    commands.spawn((
+        Name::new("SupplyDelivery: detergent"),
+        SupplyDelivery {
+            doses: DETERGENT_CASE_DOSES,
+            arrives_at,
+            arrived: false,
+        },
+        StateScoped(AppState::InGame),
+    ));
+}
+
+/// Drops delivered cases by the door, where the
+/// player has to go and click them to stock up.
+fn deliver_supplies(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut deliveries: Query<(Entity, &mut SupplyDelivery)>,
+    doors: Query<
+        &GlobalTransform,
+        With<CustomerNpcSpawner>,
+    >,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let now = clock.elapsed_minutes();
+    for (entity, mut delivery) in &mut deliveries {
+        if delivery.arrived || delivery.arrives_at > now {
+            continue;
+        }
+        let Some(door) = doors.iter().next() else {
+            warn!("no door to deliver supplies to");
+            continue;
+        };
+        info!("detergent delivered");
+        delivery.arrived = true;
+        commands.entity(entity).insert((
+            PbrBundle {
+                mesh: meshes.add(Cuboid::from_length(0.6)),
+                material: materials
+                    .add(Color::srgb(0.8, 0.6, 0.3)),
+                transform: Transform::from_translation(
+                    door.translation() + Vec3::Y * 0.3,
+                ),
+                ..default()
+            },
+            Collider::cuboid(0.6, 0.6, 0.6),
+            On::<Pointer<Click>>::run(stock_up),
+        ));
+    }
+}
+
+fn stock_up(
+    event: Listener<Pointer<Click>>,
+    mut commands: Commands,
+    deliveries: Query<(&SupplyDelivery, &GlobalTransform)>,
+    player: Query<(Entity, &GlobalTransform), With<Player>>,
+    mut stock: ResMut<DetergentStock>,
+) {
+    let delivery_entity = event.target;
+    let Ok((delivery, delivery_transform)) =
+        deliveries.get(delivery_entity)
+    else {
+        return;
+    };
+    let Ok((player, player_transform)) =
+        player.get_single()
+    else {
+        warn!("expected exactly 1 player");
+        return;
+    };
+    if player_transform
+        .translation()
+        .distance(delivery_transform.translation())
+        > SUPPLY_REACH
+    {
+        commands.trigger_targets(
+            InvalidRangeToObject {
+                object: delivery_entity,
+            },
+            player,
+        );
+        return;
+    }
+
+    stock.doses += delivery.doses;
+    info!(doses = stock.doses, "detergent restocked");
+    commands.entity(delivery_entity).despawn_recursive();
+}
+
+/// The utility company sends its bill for the day's
+/// water and power once the day is over.
+fn bill_utilities(
+    trigger: Trigger<DayEnded>,
+    mut commands: Commands,
+    mut meter: ResMut<UtilityMeter>,
+) {
+    let amount = meter.bill();
+    info!(
+        day = trigger.event().day,
+        water = meter.water,
+        power = meter.power,
+        amount,
+        "utility bill"
+    );
+    if amount > 0 {
+        commands.trigger(Transaction {
+            kind: TransactionKind::UtilityBill,
+            amount,
+            memo: format!(
+                "water {:.0} l, power {:.1} kWh",
+                meter.water, meter.power
+            ),
+        });
+    }
+    *meter = UtilityMeter::default();
+}
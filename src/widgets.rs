@@ -16,6 +16,22 @@ pub use inventory_modal::{
 };
 pub mod inventory_modal_base;
 pub use inventory_modal_base::*;
+pub mod order_board;
+pub use order_board::{OrderBoard, OrderBoardBundle};
+pub mod contract_board;
+pub use contract_board::{
+    ContractBoard, ContractBoardBundle,
+};
+pub mod machine_shop_panel;
+pub use machine_shop_panel::{
+    MachineShopPanel, MachineShopPanelBundle,
+};
+pub mod machine_upgrade_menu;
+pub use machine_upgrade_menu::{
+    MachineUpgradeMenu, MachineUpgradeMenuBundle,
+};
+pub mod day_summary;
+pub use day_summary::{DaySummary, DaySummaryBundle};
 
 pub struct WashCycleWidgetsPlugin;
 
@@ -25,6 +41,12 @@ impl Plugin for WashCycleWidgetsPlugin {
             .register_widget::<OptionsModal>()
             .register_widget::<InventoryModal>()
             .register_widget::<InventoryBaseModal>()
+            .register_widget::<OrderBoard>()
+            .register_widget::<ContractBoard>()
+            .register_widget::<MachineShopPanel>()
+            .register_widget::<MachineUpgradeMenu>()
+            .register_widget::<DaySummary>()
+            .observe(machine_upgrade_menu::close_when_sold)
             .add_systems(
                 Update,
                 (
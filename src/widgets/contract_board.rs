@@ -0,0 +1,169 @@
+use bevy::{color::palettes::tailwind::*, prelude::*};
+use bevy_mod_picking::{
+    events::{Click, Pointer},
+    prelude::On,
+};
+use woodpecker_ui::prelude::*;
+
+use super::{InventoryBaseModal, InventoryBaseModalBundle};
+use crate::{
+    assets::{FontVelloAssets, GameDataAssets},
+    customer_npc::{
+        AcceptContract, Contract, ContractOffers,
+    },
+};
+
+/// The contracts businesses are offering, and how the
+/// signed ones are going.
+#[derive(
+    Debug,
+    Component,
+    Widget,
+    Clone,
+    Default,
+    Copy,
+    PartialEq,
+    Reflect,
+)]
+#[widget_systems(update, render)]
+pub struct ContractBoard {
+    pub show_modal: bool,
+}
+
+#[derive(Bundle, Default, Clone)]
+pub struct ContractBoardBundle {
+    pub board: ContractBoard,
+    pub styles: WoodpeckerStyle,
+    pub children: WidgetChildren,
+}
+
+fn update(
+    current_widget: Res<CurrentWidget>,
+    query: Query<Entity, Changed<ContractBoard>>,
+    contracts: Query<(), Changed<Contract>>,
+    mut removed: RemovedComponents<Contract>,
+) -> bool {
+    query.contains(**current_widget)
+        || !contracts.is_empty()
+        || removed.read().count() > 0
+}
+
+fn render(
+    current_widget: Res<CurrentWidget>,
+    mut query: Query<(&ContractBoard, &mut WidgetChildren)>,
+    contracts: Query<&Contract>,
+    game_data: Res<GameDataAssets>,
+    contract_offers: Res<Assets<ContractOffers>>,
+    fonts: Res<FontVelloAssets>,
+) {
+    let Ok((my_widget, mut widget_children)) =
+        query.get_mut(**current_widget)
+    else {
+        return;
+    };
+    let Some(contract_offers) =
+        contract_offers.get(&game_data.contract_offers)
+    else {
+        warn!("contract offers are not loaded");
+        return;
+    };
+
+    let text = |content: String| {
+        (
+            ElementBundle {
+                styles: WoodpeckerStyle {
+                    font_size: 20.0,
+                    color: SLATE_950.into(),
+                    font: Some(fonts.outfit_bold.id()),
+                    ..default()
+                },
+                ..default()
+            },
+            WidgetRender::Text {
+                content,
+                word_wrap: false,
+            },
+        )
+    };
+
+    let mut rows = WidgetChildren::default();
+    for (index, terms) in
+        contract_offers.offers.iter().enumerate()
+    {
+        rows.add::<Element>(text(format!(
+            "{}: {} {} a day for {} days, {} service, \
+             condition {:.0}%",
+            terms.business,
+            terms.batch_size,
+            terms.item,
+            terms.days,
+            terms.tier.label(),
+            terms.min_condition * 100.,
+        )));
+
+        if let Some(contract) =
+            contracts.iter().find(|contract| {
+                contract.terms.business == terms.business
+            })
+        {
+            rows.add::<Element>(text(format!(
+                "signed, {} batches to go, {}/{} breaches",
+                contract.deliveries_left,
+                contract.breaches,
+                terms.max_breaches,
+            )));
+            continue;
+        }
+        rows.add::<WButton>((
+            WButtonBundle {
+                children: WidgetChildren::default()
+                    .with_child::<Element>(text(
+                        "Accept".to_string(),
+                    )),
+                ..default()
+            },
+            On::<Pointer<Click>>::run(
+                move |mut commands: Commands| {
+                    commands.trigger(AcceptContract {
+                        offer: index,
+                    });
+                },
+            ),
+        ));
+    }
+
+    widget_children.add::<InventoryBaseModal>(
+        InventoryBaseModalBundle {
+            modal: InventoryBaseModal {
+                visible: my_widget.show_modal,
+                title: "Contracts".to_string(),
+                overlay_color: SLATE_50
+                    .with_alpha(0.35)
+                    .into(),
+                ..default()
+            },
+            children: PassedChildren(
+                WidgetChildren::default()
+                    .with_child::<Element>(ElementBundle {
+                        styles: WoodpeckerStyle {
+                            display: WidgetDisplay::Flex,
+                            flex_direction:
+                                WidgetFlexDirection::Column,
+                            gap: (
+                                Units::Pixels(5.),
+                                Units::Pixels(5.),
+                            ),
+                            padding: Edge::all(10.0),
+                            width: Units::Percentage(100.0),
+                            ..default()
+                        },
+                        children: rows,
+                        ..default()
+                    }),
+            ),
+            ..default()
+        },
+    );
+
+    widget_children.apply(current_widget.as_parent());
+}
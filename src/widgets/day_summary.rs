@@ -0,0 +1,167 @@
+use bevy::{color::palettes::tailwind::*, prelude::*};
+use bevy_mod_picking::{
+    events::{Click, Pointer},
+    prelude::On,
+};
+use woodpecker_ui::prelude::*;
+
+use super::{InventoryBaseModal, InventoryBaseModalBundle};
+use crate::{
+    assets::FontVelloAssets,
+    clock::Weekday,
+    day_cycle::{ClosedForTheDay, DayStats, StartNextDay},
+    economy::Ledger,
+};
+
+/// The end of day summary, shown while the shop is
+/// `ClosedForTheDay`.
+#[derive(
+    Debug,
+    Component,
+    Widget,
+    Clone,
+    Default,
+    Copy,
+    PartialEq,
+    Reflect,
+)]
+#[widget_systems(update, render)]
+pub struct DaySummary;
+
+#[derive(Bundle, Default, Clone)]
+pub struct DaySummaryBundle {
+    pub summary: DaySummary,
+    pub styles: WoodpeckerStyle,
+    pub children: WidgetChildren,
+}
+
+fn update(
+    current_widget: Res<CurrentWidget>,
+    query: Query<Entity, Changed<DaySummary>>,
+    closed: Option<Res<ClosedForTheDay>>,
+    ledger: Res<Ledger>,
+    mut was_closed: Local<bool>,
+) -> bool {
+    let is_closed = closed.is_some();
+    let toggled = is_closed != *was_closed;
+    *was_closed = is_closed;
+    query.contains(**current_widget)
+        || toggled
+        || ledger.is_changed()
+}
+
+fn render(
+    current_widget: Res<CurrentWidget>,
+    mut query: Query<&mut WidgetChildren, With<DaySummary>>,
+    closed: Option<Res<ClosedForTheDay>>,
+    stats: Res<DayStats>,
+    ledger: Res<Ledger>,
+    fonts: Res<FontVelloAssets>,
+) {
+    let Ok(mut widget_children) =
+        query.get_mut(**current_widget)
+    else {
+        return;
+    };
+
+    let text = |content: String| {
+        (
+            ElementBundle {
+                styles: WoodpeckerStyle {
+                    font_size: 20.0,
+                    color: SLATE_950.into(),
+                    font: Some(fonts.outfit_bold.id()),
+                    ..default()
+                },
+                ..default()
+            },
+            WidgetRender::Text {
+                content,
+                word_wrap: false,
+            },
+        )
+    };
+    let dollars = |cents: i64| cents as f32 / 100.;
+
+    let mut rows = WidgetChildren::default();
+    let mut title = "Closed".to_string();
+    if let Some(closed) = &closed {
+        title = format!(
+            "Day {} ({:?}) is over",
+            closed.day + 1,
+            Weekday::from_day(closed.day)
+        );
+        let totals = ledger.totals_on(closed.day);
+        for content in [
+            format!(
+                "Revenue ${:.2}",
+                dollars(totals.income)
+            ),
+            format!(
+                "Costs ${:.2}",
+                dollars(totals.expenses)
+            ),
+            format!("Net ${:.2}", dollars(totals.net())),
+            format!(
+                "Customers served {}",
+                stats.customers_served
+            ),
+            format!(
+                "Average wait {:.0} min",
+                stats.average_wait()
+            ),
+            format!("Complaints {}", stats.complaints),
+        ] {
+            rows.add::<Element>(text(content));
+        }
+    }
+    rows.add::<WButton>((
+        WButtonBundle {
+            children: WidgetChildren::default()
+                .with_child::<Element>(text(
+                    "Next Day".to_string(),
+                )),
+            ..default()
+        },
+        On::<Pointer<Click>>::run(
+            |mut commands: Commands| {
+                commands.trigger(StartNextDay);
+            },
+        ),
+    ));
+
+    widget_children.add::<InventoryBaseModal>(
+        InventoryBaseModalBundle {
+            modal: InventoryBaseModal {
+                visible: closed.is_some(),
+                title,
+                overlay_color: SLATE_50
+                    .with_alpha(0.35)
+                    .into(),
+                ..default()
+            },
+            children: PassedChildren(
+                WidgetChildren::default()
+                    .with_child::<Element>(ElementBundle {
+                        styles: WoodpeckerStyle {
+                            display: WidgetDisplay::Flex,
+                            flex_direction:
+                                WidgetFlexDirection::Column,
+                            gap: (
+                                Units::Pixels(5.),
+                                Units::Pixels(5.),
+                            ),
+                            padding: Edge::all(10.0),
+                            width: Units::Percentage(100.0),
+                            ..default()
+                        },
+                        children: rows,
+                        ..default()
+                    }),
+            ),
+            ..default()
+        },
+    );
+
+    widget_children.apply(current_widget.as_parent());
+}
@@ -6,7 +6,12 @@ use bevy_mod_picking::{
 };
 use woodpecker_ui::prelude::*;
 
-use crate::{game_scene::Player, inventory::Inventory};
+use crate::{
+    game_scene::Player,
+    inventory::{
+        held_volume, HeldBy, InPocket, Inventory, Item,
+    },
+};
 
 use super::{InventoryBaseModal, InventoryBaseModalBundle};
 
@@ -50,7 +55,8 @@ fn render(
         &InventoryModal,
         &mut WidgetChildren,
     )>,
-    inventory_query: Query<&Inventory, With<Player>>,
+    player_query: Query<(Entity, &Inventory), With<Player>>,
+    item_query: Query<(&Item, &HeldBy, Option<&InPocket>)>,
 ) {
     let Ok((my_widget, mut widget_children)) =
         query.get_mut(**current_widget)
@@ -89,17 +95,27 @@ fn render(
     // ));
 
     let mut items = WidgetChildren::default();
-    let Ok(inventory) = inventory_query.get_single() else {
+    let Ok((player, inventory)) = player_query.get_single()
+    else {
         warn!("no player inventory");
         return;
     };
-    for item in &inventory.items {
+    // pocket contents stay out of sight until the
+    // pockets are checked
+    for (item, _, _) in
+        item_query.iter().filter(|(_, held_by, in_pocket)| {
+            held_by.0 == player && in_pocket.is_none()
+        })
+    {
+        // bulkier items take up more of the grid
         items.add::<Element>((
             ElementBundle {
                 styles: WoodpeckerStyle {
                     font_size: 20.0,
                     background_color: SKY_400.into(),
-                    width: Units::Pixels(20.),
+                    width: Units::Pixels(
+                        20. * item.volume as f32,
+                    ),
                     height: Units::Pixels(20.),
                     ..default()
                 },
@@ -113,7 +129,11 @@ fn render(
         InventoryBaseModalBundle {
             modal: InventoryBaseModal {
                 visible: my_widget.show_modal,
-                title: "Inventory".into(),
+                title: format!(
+                    "Inventory {}/{}",
+                    held_volume(&item_query, player),
+                    inventory.max_volume
+                ),
                 overlay_color: SLATE_50
                     .with_alpha(0.35)
                     .into(),
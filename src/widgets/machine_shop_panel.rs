@@ -0,0 +1,144 @@
+use bevy::{color::palettes::tailwind::*, prelude::*};
+use woodpecker_ui::prelude::*;
+
+use crate::{
+    assets::{FontVelloAssets, GameDataAssets},
+    economy::Wallet,
+    grid::SelectedMachineKind,
+    machine_shop::MachineCatalog,
+    processing::ProcessingStep,
+    states::GameMode,
+};
+
+/// Prices of the machines for sale and what's in the
+/// `Wallet`, shown while placing machines.
+#[derive(
+    Debug,
+    Component,
+    Widget,
+    Clone,
+    Default,
+    Copy,
+    PartialEq,
+    Reflect,
+)]
+#[widget_systems(update, render)]
+pub struct MachineShopPanel;
+
+#[derive(Bundle, Default, Clone)]
+pub struct MachineShopPanelBundle {
+    pub panel: MachineShopPanel,
+    pub styles: WoodpeckerStyle,
+    pub children: WidgetChildren,
+}
+
+fn update(
+    current_widget: Res<CurrentWidget>,
+    query: Query<Entity, Changed<MachineShopPanel>>,
+    game_mode: Option<Res<State<GameMode>>>,
+    selected: Res<SelectedMachineKind>,
+    wallet: Res<Wallet>,
+) -> bool {
+    query.contains(**current_widget)
+        || game_mode.is_some_and(|mode| mode.is_changed())
+        || selected.is_changed()
+        || wallet.is_changed()
+}
+
+fn render(
+    current_widget: Res<CurrentWidget>,
+    mut query: Query<
+        &mut WidgetChildren,
+        With<MachineShopPanel>,
+    >,
+    game_mode: Option<Res<State<GameMode>>>,
+    selected: Res<SelectedMachineKind>,
+    wallet: Res<Wallet>,
+    game_data: Res<GameDataAssets>,
+    machine_catalogs: Res<Assets<MachineCatalog>>,
+    fonts: Res<FontVelloAssets>,
+) {
+    let Ok(mut widget_children) =
+        query.get_mut(**current_widget)
+    else {
+        return;
+    };
+    let placing = game_mode.is_some_and(|mode| {
+        *mode.get() == GameMode::VirtualGridPlacement
+    });
+    let Some(catalog) =
+        machine_catalogs.get(&game_data.machine_catalog)
+    else {
+        return;
+    };
+
+    // nothing to show outside of placement
+    if !placing {
+        widget_children.apply(current_widget.as_parent());
+        return;
+    }
+
+    let mut rows = WidgetChildren::default();
+    let lines = std::iter::once((
+        format!(
+            "Wallet ${:.2}",
+            wallet.balance() as f32 / 100.
+        ),
+        SLATE_950,
+    ))
+    .chain(catalog.machines.iter().map(|listing| {
+        // the number key that selects the machine
+        let key = ProcessingStep::ALL
+            .iter()
+            .position(|step| *step == listing.step)
+            .map_or(0, |index| index + 1);
+        (
+            format!(
+                "{key} {} ${:.2}",
+                listing.step.machine_name(),
+                listing.price as f32 / 100.
+            ),
+            if listing.step == selected.0 {
+                SKY_600
+            } else if !wallet.can_afford(listing.price) {
+                RED_600
+            } else {
+                SLATE_950
+            },
+        )
+    }));
+    for (content, color) in lines {
+        rows.add::<Element>((
+            ElementBundle {
+                styles: WoodpeckerStyle {
+                    font_size: 20.0,
+                    color: color.into(),
+                    font: Some(fonts.outfit_bold.id()),
+                    ..default()
+                },
+                ..default()
+            },
+            WidgetRender::Text {
+                content,
+                word_wrap: false,
+            },
+        ));
+    }
+
+    widget_children.add::<Element>(ElementBundle {
+        styles: WoodpeckerStyle {
+            display: WidgetDisplay::Flex,
+            flex_direction: WidgetFlexDirection::Column,
+            gap: (Units::Pixels(5.), Units::Pixels(5.)),
+            padding: Edge::all(10.0),
+            background_color: SLATE_50
+                .with_alpha(0.8)
+                .into(),
+            ..default()
+        },
+        children: rows,
+        ..default()
+    });
+
+    widget_children.apply(current_widget.as_parent());
+}
@@ -0,0 +1,242 @@
+use bevy::{color::palettes::tailwind::*, prelude::*};
+use bevy_mod_picking::{
+    events::{Click, Pointer},
+    prelude::On,
+};
+use woodpecker_ui::prelude::*;
+
+use super::{InventoryBaseModal, InventoryBaseModalBundle};
+use crate::{
+    assets::{FontVelloAssets, GameDataAssets},
+    economy::Wallet,
+    grid::{MachineKind, MaxLoad, UpgradeMachine},
+    inventory::Inventory,
+    machine_shop::{MachineCatalog, MachineTier},
+};
+
+/// The upgrade menu of the machine that was middle
+/// clicked.
+#[derive(
+    Debug,
+    Component,
+    Widget,
+    Clone,
+    Default,
+    Copy,
+    PartialEq,
+    Reflect,
+)]
+#[widget_systems(update, render)]
+pub struct MachineUpgradeMenu {
+    /// The machine the menu is open for, if it is
+    pub machine: Option<Entity>,
+}
+
+#[derive(Bundle, Default, Clone)]
+pub struct MachineUpgradeMenuBundle {
+    pub menu: MachineUpgradeMenu,
+    pub styles: WoodpeckerStyle,
+    pub children: WidgetChildren,
+}
+
+/// Closes menus whose machine was sold
+pub(super) fn close_when_sold(
+    trigger: Trigger<OnRemove, MachineKind>,
+    mut menus: Query<&mut MachineUpgradeMenu>,
+) {
+    for mut menu in &mut menus {
+        if menu.machine == Some(trigger.entity()) {
+            menu.machine = None;
+        }
+    }
+}
+
+fn update(
+    current_widget: Res<CurrentWidget>,
+    query: Query<Entity, Changed<MachineUpgradeMenu>>,
+    tiers: Query<(), Changed<MachineTier>>,
+    wallet: Res<Wallet>,
+) -> bool {
+    query.contains(**current_widget)
+        || !tiers.is_empty()
+        || wallet.is_changed()
+}
+
+fn render(
+    current_widget: Res<CurrentWidget>,
+    mut query: Query<(
+        &MachineUpgradeMenu,
+        &mut WidgetChildren,
+    )>,
+    machines: Query<(
+        &MachineKind,
+        &MachineTier,
+        &Inventory,
+        &MaxLoad,
+    )>,
+    wallet: Res<Wallet>,
+    game_data: Res<GameDataAssets>,
+    machine_catalogs: Res<Assets<MachineCatalog>>,
+    fonts: Res<FontVelloAssets>,
+) {
+    let Ok((my_widget, mut widget_children)) =
+        query.get_mut(**current_widget)
+    else {
+        return;
+    };
+    let my_widget_entity = **current_widget;
+
+    let text = |content: String, color: Srgba| {
+        (
+            ElementBundle {
+                styles: WoodpeckerStyle {
+                    font_size: 20.0,
+                    color: color.into(),
+                    font: Some(fonts.outfit_bold.id()),
+                    ..default()
+                },
+                ..default()
+            },
+            WidgetRender::Text {
+                content,
+                word_wrap: false,
+            },
+        )
+    };
+
+    let mut rows = WidgetChildren::default();
+    let mut title = "Machine".to_string();
+    if let Some((
+        machine,
+        (kind, tier, inventory, max_load),
+    )) = my_widget.machine.and_then(|machine| {
+        Some((machine, machines.get(machine).ok()?))
+    }) {
+        title = format!(
+            "{} (tier {})",
+            kind.0.machine_name(),
+            tier.0
+        );
+        rows.add::<Element>(text(
+            format!(
+                "room for {}, up to {:.1} kg",
+                inventory.max_volume, max_load.0
+            ),
+            SLATE_950,
+        ));
+
+        let next_upgrade = machine_catalogs
+            .get(&game_data.machine_catalog)
+            .and_then(|catalog| catalog.listing(kind.0))
+            .and_then(|listing| {
+                listing.next_upgrade(*tier)
+            });
+        match next_upgrade {
+            Some(upgrade) => {
+                let affordable =
+                    wallet.can_afford(upgrade.price);
+                rows.add::<Element>(text(
+                    format!(
+                        "{}: room for {}, up to {:.1} kg, \
+                         {:.0}s cycles, +{:.0}% quality",
+                        upgrade.name,
+                        MaxLoad(upgrade.max_load).volume(),
+                        upgrade.max_load,
+                        upgrade.cycle_seconds,
+                        upgrade.quality_bonus * 100.,
+                    ),
+                    SLATE_950,
+                ));
+                rows.add::<WButton>((
+                    WButtonBundle {
+                        children: WidgetChildren::default()
+                            .with_child::<Element>(text(
+                                format!(
+                                    "Upgrade ${:.2}",
+                                    upgrade.price as f32
+                                        / 100.
+                                ),
+                                if affordable {
+                                    SLATE_950
+                                } else {
+                                    RED_600
+                                },
+                            )),
+                        ..default()
+                    },
+                    On::<Pointer<Click>>::run(
+                        move |mut commands: Commands| {
+                            commands.trigger(
+                                UpgradeMachine {
+                                    machine_entity: machine,
+                                },
+                            );
+                        },
+                    ),
+                ));
+            }
+            None => {
+                rows.add::<Element>(text(
+                    "fully upgraded".to_string(),
+                    SLATE_950,
+                ));
+            }
+        }
+    }
+    rows.add::<WButton>((
+        WButtonBundle {
+            children: WidgetChildren::default()
+                .with_child::<Element>(text(
+                    "Close".to_string(),
+                    SLATE_950,
+                )),
+            ..default()
+        },
+        On::<Pointer<Click>>::run(
+            move |mut query: Query<
+                &mut MachineUpgradeMenu,
+            >| {
+                if let Ok(mut my_widget) =
+                    query.get_mut(my_widget_entity)
+                {
+                    my_widget.machine = None;
+                }
+            },
+        ),
+    ));
+
+    widget_children.add::<InventoryBaseModal>(
+        InventoryBaseModalBundle {
+            modal: InventoryBaseModal {
+                visible: my_widget.machine.is_some(),
+                title,
+                overlay_color: SLATE_50
+                    .with_alpha(0.35)
+                    .into(),
+                ..default()
+            },
+            children: PassedChildren(
+                WidgetChildren::default()
+                    .with_child::<Element>(ElementBundle {
+                        styles: WoodpeckerStyle {
+                            display: WidgetDisplay::Flex,
+                            flex_direction:
+                                WidgetFlexDirection::Column,
+                            gap: (
+                                Units::Pixels(5.),
+                                Units::Pixels(5.),
+                            ),
+                            padding: Edge::all(10.0),
+                            width: Units::Percentage(100.0),
+                            ..default()
+                        },
+                        children: rows,
+                        ..default()
+                    }),
+            ),
+            ..default()
+        },
+    );
+
+    widget_children.apply(current_widget.as_parent());
+}
@@ -0,0 +1,130 @@
+use bevy::{color::palettes::tailwind::*, prelude::*};
+use woodpecker_ui::prelude::*;
+
+use crate::{
+    assets::FontVelloAssets, clock::GameClock,
+    customer_npc::Order,
+};
+
+/// In-game minutes between refreshes of the time left
+/// on each order
+const REFRESH_MINUTES: f32 = 10.;
+/// Orders due sooner than this are highlighted
+const DUE_SOON_MINUTES: f32 = 30.;
+
+/// Every open order, soonest deadline first.
+#[derive(
+    Debug,
+    Component,
+    Widget,
+    Clone,
+    Default,
+    Copy,
+    PartialEq,
+    Reflect,
+)]
+#[widget_systems(update, render)]
+pub struct OrderBoard;
+
+#[derive(Bundle, Default, Clone)]
+pub struct OrderBoardBundle {
+    pub board: OrderBoard,
+    pub styles: WoodpeckerStyle,
+    pub children: WidgetChildren,
+}
+
+fn update(
+    current_widget: Res<CurrentWidget>,
+    query: Query<Entity, Changed<OrderBoard>>,
+    orders: Query<(), Changed<Order>>,
+    mut removed: RemovedComponents<Order>,
+    clock: Res<GameClock>,
+    mut last_refresh: Local<u32>,
+) -> bool {
+    let refresh =
+        (clock.elapsed_minutes() / REFRESH_MINUTES) as u32;
+    let ticked = refresh != *last_refresh;
+    *last_refresh = refresh;
+
+    query.contains(**current_widget)
+        || !orders.is_empty()
+        || removed.read().count() > 0
+        || ticked
+}
+
+fn render(
+    current_widget: Res<CurrentWidget>,
+    mut query: Query<&mut WidgetChildren, With<OrderBoard>>,
+    orders: Query<&Order>,
+    clock: Res<GameClock>,
+    fonts: Res<FontVelloAssets>,
+) {
+    let Ok(mut widget_children) =
+        query.get_mut(**current_widget)
+    else {
+        return;
+    };
+
+    let now = clock.elapsed_minutes();
+    let mut orders = orders.iter().collect::<Vec<_>>();
+    orders
+        .sort_by(|a, b| a.deadline.total_cmp(&b.deadline));
+
+    let mut rows = WidgetChildren::default();
+    for order in orders {
+        let left = order.deadline - now;
+        let (status, color) = if order.finished_at.is_some()
+        {
+            ("ready".to_string(), GREEN_600)
+        } else if left < 0. {
+            (format!("{} min late", -left as u32), RED_600)
+        } else if left < DUE_SOON_MINUTES {
+            (
+                format!("due in {} min", left as u32),
+                AMBER_500,
+            )
+        } else {
+            (
+                format!("due in {} min", left as u32),
+                SLATE_950,
+            )
+        };
+        rows.add::<Element>((
+            ElementBundle {
+                styles: WoodpeckerStyle {
+                    font_size: 20.0,
+                    color: color.into(),
+                    font: Some(fonts.outfit_bold.id()),
+                    ..default()
+                },
+                ..default()
+            },
+            WidgetRender::Text {
+                content: format!(
+                    "{} | {} | {} items | {status}",
+                    order.archetype,
+                    order.tier.label(),
+                    order.item_count,
+                ),
+                word_wrap: false,
+            },
+        ));
+    }
+
+    widget_children.add::<Element>(ElementBundle {
+        styles: WoodpeckerStyle {
+            display: WidgetDisplay::Flex,
+            flex_direction: WidgetFlexDirection::Column,
+            gap: (Units::Pixels(5.), Units::Pixels(5.)),
+            padding: Edge::all(10.0),
+            background_color: SLATE_50
+                .with_alpha(0.8)
+                .into(),
+            ..default()
+        },
+        children: rows,
+        ..default()
+    });
+
+    widget_children.apply(current_widget.as_parent());
+}